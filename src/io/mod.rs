@@ -0,0 +1,2 @@
+pub(crate) mod scene_json;
+pub(crate) mod scene_yaml;
@@ -0,0 +1,101 @@
+use crate::scene::camera::Camera;
+use crate::scene::world::World;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Scene<C, W> {
+    camera: C,
+    world: W,
+}
+
+pub(crate) fn save_scene(world: &World, camera: &Camera) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Scene { camera, world })
+}
+
+pub(crate) fn load_scene(json: &str) -> serde_json::Result<(World, Camera)> {
+    let scene: Scene<Camera, World> = serde_json::from_str(json)?;
+
+    Ok((scene.world, scene.camera))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{scaling, translation, view_transform};
+    use crate::math::tuple::Tuple;
+    use crate::pattern::stripe::StripePattern;
+    use crate::primitives::cylinder::Cylinder;
+    use crate::primitives::shape::{Shape, ShapeKind};
+    use crate::primitives::sphere::Sphere;
+    use crate::scene::shading::color::Color;
+    use crate::scene::sky::SkyBackground;
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_saved_world_renders_the_same_after_loading() {
+        let mut world = World::demo();
+        world.background = Some(SkyBackground::new(
+            Color::new(0.1, 0.2, 0.8),
+            Color::new(0.9, 0.9, 1.),
+        ));
+        let mut camera = Camera::new(11, 11, PI / 2.);
+        camera.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        let json = save_scene(&world, &camera).unwrap();
+        let (loaded_world, loaded_camera) = load_scene(&json).unwrap();
+
+        assert_eq!(2, loaded_world.objects().len());
+        assert_eq!(
+            camera.render(Arc::new(world)).hash(),
+            loaded_camera.render(Arc::new(loaded_world)).hash()
+        );
+    }
+
+    #[test]
+    fn test_unbounded_cylinder_survives_round_trip() {
+        let mut world = World::new();
+        world.add_object(Cylinder::new().transformed(translation(1., 2., 3.)));
+
+        let json = save_scene(&world, &Camera::new(10, 10, 1.)).unwrap();
+        let (loaded, _) = load_scene(&json).unwrap();
+
+        assert_eq!(
+            Some(ShapeKind::Cylinder {
+                min: None,
+                max: None,
+                closed: false
+            }),
+            loaded.objects()[0].kind()
+        );
+        assert_eq!(
+            translation(1., 2., 3.),
+            loaded.objects()[0].get_props().get_transform()
+        );
+    }
+
+    #[test]
+    fn test_saving_a_pattern_fails() {
+        let mut world = World::new();
+        let mut sphere = Sphere::new();
+        sphere
+            .mut_props()
+            .set_pattern(Box::new(StripePattern::new(Color::white(), Color::black())));
+        world.add_object(sphere);
+
+        assert!(save_scene(&world, &Camera::new(10, 10, 1.)).is_err());
+    }
+
+    #[test]
+    fn test_loading_singular_camera_transform_fails() {
+        let json = save_scene(&World::new(), &Camera::new(10, 10, 1.)).unwrap();
+        let mut scene: serde_json::Value = serde_json::from_str(&json).unwrap();
+        scene["camera"]["transform"] = serde_json::to_value(scaling(0., 1., 1.)).unwrap();
+
+        assert!(load_scene(&scene.to_string()).is_err());
+    }
+}
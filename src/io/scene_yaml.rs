@@ -0,0 +1,345 @@
+use crate::math::matrix::M4;
+use crate::math::transformation::view_transform;
+use crate::math::tuple::Tuple;
+use crate::primitives::cube::Cube;
+use crate::primitives::cylinder::Cylinder;
+use crate::primitives::plane::Plane;
+use crate::primitives::shape::Shape;
+use crate::primitives::sphere::Sphere;
+use crate::scene::camera::Camera;
+use crate::scene::shading::color::Color;
+use crate::scene::shading::light::PointLight;
+use crate::scene::shading::material::{Material, MaterialBuilder};
+use crate::scene::world::World;
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+
+#[derive(Debug)]
+pub(crate) enum SceneError {
+    Yaml(serde_yaml::Error),
+    // the scene has to contain exactly one camera
+    MissingCamera,
+    DuplicateCamera,
+    UnknownItem(String),
+    UnknownTransform(String),
+    // a required key is missing or its value has the wrong type
+    InvalidValue(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Yaml(error) => write!(f, "invalid yaml: {}", error),
+            SceneError::MissingCamera => write!(f, "scene has no camera"),
+            SceneError::DuplicateCamera => write!(f, "scene has more than one camera"),
+            SceneError::UnknownItem(name) => write!(f, "unknown item to add: {}", name),
+            SceneError::UnknownTransform(name) => write!(f, "unknown transform: {}", name),
+            SceneError::InvalidValue(key) => write!(f, "missing or invalid value for {}", key),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<serde_yaml::Error> for SceneError {
+    fn from(error: serde_yaml::Error) -> SceneError {
+        SceneError::Yaml(error)
+    }
+}
+
+// loads a subset of the ray tracer challenge scene format: a list of items, each with an
+// `add` key naming a camera, light or shape
+pub(crate) fn load_scene(source: &str) -> Result<(World, Camera), SceneError> {
+    let items: Vec<Mapping> = serde_yaml::from_str(source)?;
+    let mut world = World::new();
+    let mut camera = None;
+
+    for item in &items {
+        let kind = string(item, "add")?;
+
+        match kind {
+            "camera" if camera.is_some() => return Err(SceneError::DuplicateCamera),
+            "camera" => camera = Some(parse_camera(item)?),
+            "light" => world.add_light(PointLight::new(
+                point(item, "at")?,
+                color(item, "intensity")?,
+            )),
+            _ => world.add_boxed_object(parse_shape(kind, item)?),
+        }
+    }
+
+    match camera {
+        Some(camera) => Ok((world, camera)),
+        None => Err(SceneError::MissingCamera),
+    }
+}
+
+fn parse_camera(item: &Mapping) -> Result<Camera, SceneError> {
+    let mut camera = Camera::new(
+        size(item, "width")?,
+        size(item, "height")?,
+        number(item, "field-of-view")?,
+    );
+    let (x, y, z) = triple(item, "up")?;
+    let transform = view_transform(
+        point(item, "from")?,
+        point(item, "to")?,
+        Tuple::direction(x, y, z),
+    );
+    // an up vector parallel to the view direction has no orientation
+    if transform.inverse().is_none() {
+        return Err(SceneError::InvalidValue("up".to_string()));
+    }
+    camera.set_transform(transform);
+
+    Ok(camera)
+}
+
+fn size(item: &Mapping, key: &str) -> Result<usize, SceneError> {
+    let value = number(item, key)?;
+    if value < 1. || value.fract() != 0. {
+        return Err(SceneError::InvalidValue(key.to_string()));
+    }
+
+    Ok(value as usize)
+}
+
+fn parse_shape(kind: &str, item: &Mapping) -> Result<Box<dyn Shape>, SceneError> {
+    let mut shape: Box<dyn Shape> = match kind {
+        "sphere" => Box::new(Sphere::new()),
+        "plane" => Box::new(Plane::new()),
+        "cube" => Box::new(Cube::new()),
+        "cylinder" => {
+            let mut cylinder = Cylinder::with_min_max(
+                optional_number(item, "min")?.unwrap_or(f64::NEG_INFINITY),
+                optional_number(item, "max")?.unwrap_or(f64::INFINITY),
+            );
+            if item.get("closed").and_then(Value::as_bool) == Some(true) {
+                cylinder.close();
+            }
+            Box::new(cylinder)
+        }
+        _ => return Err(SceneError::UnknownItem(kind.to_string())),
+    };
+
+    if let Some(material) = item.get("material") {
+        let material = material
+            .as_mapping()
+            .ok_or_else(|| SceneError::InvalidValue("material".to_string()))?;
+        shape.mut_props().set_material(parse_material(material)?);
+    }
+    if let Some(transform) = item.get("transform") {
+        shape.mut_props().set_transform(parse_transform(transform)?);
+    }
+
+    Ok(shape)
+}
+
+fn parse_material(item: &Mapping) -> Result<Material, SceneError> {
+    let mut builder = MaterialBuilder::new();
+
+    if item.contains_key("color") {
+        builder = builder.color(color(item, "color")?);
+    }
+    if let Some(ambient) = optional_number(item, "ambient")? {
+        builder = builder.ambient(ambient);
+    }
+    if let Some(diffuse) = optional_number(item, "diffuse")? {
+        builder = builder.diffuse(diffuse);
+    }
+    if let Some(specular) = optional_number(item, "specular")? {
+        builder = builder.specular(specular);
+    }
+    if let Some(shininess) = optional_number(item, "shininess")? {
+        builder = builder.shininess(shininess);
+    }
+    if let Some(reflective) = optional_number(item, "reflective")? {
+        builder = builder.reflective(reflective);
+    }
+    if let Some(transparency) = optional_number(item, "transparency")? {
+        builder = builder.transparency(transparency);
+    }
+    if let Some(refractive_index) = optional_number(item, "refractive-index")? {
+        builder = builder.refractive_index(refractive_index);
+    }
+
+    Ok(builder.build())
+}
+
+// each entry is like [ translate, 1, 2, 3 ], applied in the listed order
+fn parse_transform(value: &Value) -> Result<M4, SceneError> {
+    let invalid = || SceneError::InvalidValue("transform".to_string());
+    let mut transform = M4::transform();
+
+    for step in value.as_sequence().ok_or_else(invalid)? {
+        let step = step.as_sequence().ok_or_else(invalid)?;
+        let name = step.first().and_then(Value::as_str).ok_or_else(invalid)?;
+        let args = step[1..]
+            .iter()
+            .map(|arg| arg.as_f64().ok_or_else(invalid))
+            .collect::<Result<Vec<f64>, SceneError>>()?;
+        let arg = |index: usize| args.get(index).copied().ok_or_else(invalid);
+
+        transform = match name {
+            "translate" => transform.then_translate(arg(0)?, arg(1)?, arg(2)?),
+            "scale" => transform.then_scale(arg(0)?, arg(1)?, arg(2)?),
+            "rotate-x" => transform.then_rotate_x(arg(0)?),
+            "rotate-y" => transform.then_rotate_y(arg(0)?),
+            "rotate-z" => transform.then_rotate_z(arg(0)?),
+            "shear" => transform.then_shear(arg(0)?, arg(1)?, arg(2)?, arg(3)?, arg(4)?, arg(5)?),
+            _ => return Err(SceneError::UnknownTransform(name.to_string())),
+        };
+    }
+
+    Ok(transform)
+}
+
+fn string<'a>(item: &'a Mapping, key: &str) -> Result<&'a str, SceneError> {
+    item.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| SceneError::InvalidValue(key.to_string()))
+}
+
+fn number(item: &Mapping, key: &str) -> Result<f64, SceneError> {
+    optional_number(item, key)?.ok_or_else(|| SceneError::InvalidValue(key.to_string()))
+}
+
+fn optional_number(item: &Mapping, key: &str) -> Result<Option<f64>, SceneError> {
+    match item.get(key) {
+        Some(value) => value
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| SceneError::InvalidValue(key.to_string())),
+        None => Ok(None),
+    }
+}
+
+fn triple(item: &Mapping, key: &str) -> Result<(f64, f64, f64), SceneError> {
+    let invalid = || SceneError::InvalidValue(key.to_string());
+    let values = item
+        .get(key)
+        .and_then(Value::as_sequence)
+        .ok_or_else(invalid)?;
+
+    match values.as_slice() {
+        [x, y, z] => Ok((
+            x.as_f64().ok_or_else(invalid)?,
+            y.as_f64().ok_or_else(invalid)?,
+            z.as_f64().ok_or_else(invalid)?,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+fn point(item: &Mapping, key: &str) -> Result<Tuple, SceneError> {
+    let (x, y, z) = triple(item, key)?;
+
+    Ok(Tuple::point(x, y, z))
+}
+
+fn color(item: &Mapping, key: &str) -> Result<Color, SceneError> {
+    let (r, g, b) = triple(item, key)?;
+
+    Ok(Color::new(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{scaling, translation};
+
+    const SCENE: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [ -6, 6, -10 ]
+  to: [ 6, 0, 6 ]
+  up: [ -0.45, 1, 0 ]
+
+- add: light
+  at: [ 50, 100, -50 ]
+  intensity: [ 1, 1, 1 ]
+
+- add: plane
+  material:
+    color: [ 1, 1, 1 ]
+    reflective: 0.2
+
+- add: sphere
+  material:
+    color: [ 0.373, 0.404, 0.550 ]
+    diffuse: 0.2
+    ambient: 0.0
+  transform:
+    - [ scale, 0.5, 0.5, 0.5 ]
+    - [ translate, 1, -1, 1 ]
+
+- add: cylinder
+  min: 0
+  max: 1
+  closed: true
+";
+
+    #[test]
+    fn test_load_scene_from_yaml() {
+        let (world, camera) = load_scene(SCENE).unwrap();
+
+        assert_eq!(3, world.objects().len());
+        assert_eq!(1, world.lights.len());
+        assert_eq!(0.785, camera.get_fov());
+        assert_eq!(
+            translation(1., -1., 1.) * scaling(0.5, 0.5, 0.5),
+            world.objects()[1].get_props().get_transform()
+        );
+        assert_eq!(
+            Color::new(0.373, 0.404, 0.55),
+            world.objects()[1].get_props().get_material().get_color()
+        );
+    }
+
+    #[test]
+    fn test_load_scene_reports_errors() {
+        assert!(matches!(
+            load_scene("- add: sphere"),
+            Err(SceneError::MissingCamera)
+        ));
+        assert!(matches!(
+            load_scene("- add: teapot"),
+            Err(SceneError::UnknownItem(_))
+        ));
+        assert!(matches!(
+            load_scene("- add: light\n  at: [ 1, 2 ]"),
+            Err(SceneError::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_scene_rejects_invalid_cameras() {
+        let camera = |width: &str, up: &str| {
+            format!(
+                "- add: camera\n  width: {}\n  height: 50\n  field-of-view: 0.785\n  \
+                 from: [ 0, 0, -5 ]\n  to: [ 0, 0, 0 ]\n  up: {}\n",
+                width, up
+            )
+        };
+
+        assert!(load_scene(&camera("100", "[ 0, 1, 0 ]")).is_ok());
+        assert!(matches!(
+            load_scene(&camera("100", "[ 0, 0, 1 ]")),
+            Err(SceneError::InvalidValue(key)) if key == "up"
+        ));
+        assert!(matches!(
+            load_scene(&camera("-100", "[ 0, 1, 0 ]")),
+            Err(SceneError::InvalidValue(key)) if key == "width"
+        ));
+        assert!(matches!(
+            load_scene(&camera("100.5", "[ 0, 1, 0 ]")),
+            Err(SceneError::InvalidValue(key)) if key == "width"
+        ));
+        assert!(matches!(
+            load_scene(&camera("100", "[ 0, 1, 0 ]").repeat(2)),
+            Err(SceneError::DuplicateCamera)
+        ));
+    }
+}
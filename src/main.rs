@@ -1,3 +1,4 @@
+mod io;
 mod math;
 mod pattern;
 mod primitives;
@@ -1,10 +1,16 @@
-use crate::math::f64_eq;
 use crate::math::tuple::Tuple;
-use std::ops::Mul;
+use crate::math::{f64_eq, f64_eq_eps, EPSILON};
+use serde::{Deserialize, Serialize};
+use std::ops::{Mul, MulAssign};
 
-#[derive(Copy, Clone, Debug)]
+// saved as the plain 16 values, the determinant is computed again when it is needed
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "[f64; 16]", into = "[f64; 16]")]
 pub(crate) struct M4 {
     data: [f64; 16],
+    // known without computing it for the identity, products and inverses of matrices with a
+    // known determinant. Otherwise (None) it is computed when needed, e.g. after set.
+    determinant: Option<f64>,
 }
 
 impl M4 {
@@ -13,6 +19,7 @@ impl M4 {
             data: [
                 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
             ],
+            determinant: Some(1.),
         }
     }
 
@@ -22,6 +29,34 @@ impl M4 {
         self.data[multiplier + column]
     }
 
+    pub(crate) fn from_rows(r0: Tuple, r1: Tuple, r2: Tuple, r3: Tuple) -> M4 {
+        let mut data = [0.; 16];
+        for (i, row) in [r0, r1, r2, r3].iter().enumerate() {
+            data[i * 4..i * 4 + 4].copy_from_slice(&row.as_array());
+        }
+
+        M4::from(data)
+    }
+
+    // rows and columns as tuples, the fourth value ends up in w
+    pub(crate) fn row(&self, row: usize) -> Tuple {
+        Tuple::new(
+            self.get(row, 0),
+            self.get(row, 1),
+            self.get(row, 2),
+            self.get(row, 3),
+        )
+    }
+
+    pub(crate) fn col(&self, column: usize) -> Tuple {
+        Tuple::new(
+            self.get(0, column),
+            self.get(1, column),
+            self.get(2, column),
+            self.get(3, column),
+        )
+    }
+
     pub(crate) fn set(&mut self, value: f64, row: usize, col: usize) {
         let index = row * 4 + col;
         if index > 15 {
@@ -29,6 +64,14 @@ impl M4 {
         }
 
         self.data[index] = value;
+        self.determinant = None;
+    }
+
+    pub(crate) fn approx_eq(&self, other: &M4, eps: f64) -> bool {
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .all(|(a, b)| f64_eq_eps(*a, *b, eps))
     }
 
     pub(crate) fn transpose(&self) -> M4 {
@@ -40,7 +83,10 @@ impl M4 {
             }
         }
 
-        M4::from(data)
+        M4 {
+            data,
+            determinant: self.determinant,
+        }
     }
 
     fn submatrix(&self, row: usize, col: usize) -> M3 {
@@ -68,6 +114,11 @@ impl M4 {
     }
 
     fn determinant(&self) -> f64 {
+        self.determinant
+            .unwrap_or_else(|| self.compute_determinant())
+    }
+
+    fn compute_determinant(&self) -> f64 {
         let mut d = 0.;
 
         for c in 0..4 {
@@ -77,12 +128,16 @@ impl M4 {
         d
     }
 
+    // a matrix with NaN entries has a NaN determinant, which can't be divided by either
     fn is_invertible(&self) -> bool {
-        self.determinant() != 0.
+        let determinant = self.determinant();
+
+        determinant != 0. && !determinant.is_nan()
     }
 
     pub(crate) fn inverse(&self) -> Option<M4> {
-        if !self.is_invertible() {
+        let determinant = self.determinant();
+        if determinant == 0. || determinant.is_nan() {
             return None;
         }
         let mut data = [0.; 16];
@@ -90,11 +145,14 @@ impl M4 {
         for row in 0..4 {
             for col in 0..4 {
                 let c = self.cofactor(row, col);
-                data[col * 4 + row] = c / self.determinant();
+                data[col * 4 + row] = c / determinant;
             }
         }
 
-        Some(M4::from(data))
+        Some(M4 {
+            data,
+            determinant: Some(1. / determinant),
+        })
     }
 }
 
@@ -108,18 +166,22 @@ impl Mul for M4 {
     type Output = M4;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let mut result = M4::from([0.; 16]);
+        multiply(&self, &rhs)
+    }
+}
 
-        for r in 0..4 {
-            for c in 0..4 {
-                result.data[r * 4 + c] = self.get(r, 0) * rhs.get(0, c)
-                    + self.get(r, 1) * rhs.get(1, c)
-                    + self.get(r, 2) * rhs.get(2, c)
-                    + self.get(r, 3) * rhs.get(3, c);
-            }
-        }
+// borrowed variants, so hot paths can multiply without copying the matrices around
+impl Mul<&M4> for &M4 {
+    type Output = M4;
 
-        result
+    fn mul(self, rhs: &M4) -> Self::Output {
+        multiply(self, rhs)
+    }
+}
+
+impl MulAssign for M4 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = multiply(self, &rhs);
     }
 }
 
@@ -127,47 +189,74 @@ impl Mul<Tuple> for M4 {
     type Output = Tuple;
 
     fn mul(self, rhs: Tuple) -> Self::Output {
-        let mut result = [0.; 4];
+        multiply_tuple(&self, &rhs)
+    }
+}
 
-        for r in 0..4 {
-            let t = Tuple::new(
-                self.get(r, 0),
-                self.get(r, 1),
-                self.get(r, 2),
-                self.get(r, 3),
-            );
-
-            result[r] = t.dot(rhs);
+impl Mul<Tuple> for &M4 {
+    type Output = Tuple;
+
+    fn mul(self, rhs: Tuple) -> Self::Output {
+        multiply_tuple(self, &rhs)
+    }
+}
+
+impl Mul<&Tuple> for &M4 {
+    type Output = Tuple;
+
+    fn mul(self, rhs: &Tuple) -> Self::Output {
+        multiply_tuple(self, rhs)
+    }
+}
+
+fn multiply(lhs: &M4, rhs: &M4) -> M4 {
+    let mut data = [0.; 16];
+
+    for r in 0..4 {
+        for c in 0..4 {
+            data[r * 4 + c] = lhs.get(r, 0) * rhs.get(0, c)
+                + lhs.get(r, 1) * rhs.get(1, c)
+                + lhs.get(r, 2) * rhs.get(2, c)
+                + lhs.get(r, 3) * rhs.get(3, c);
         }
+    }
 
-        Tuple::new(result[0], result[1], result[2], result[3])
+    M4 {
+        data,
+        determinant: lhs.determinant.zip(rhs.determinant).map(|(l, r)| l * r),
     }
 }
 
+fn multiply_tuple(lhs: &M4, rhs: &Tuple) -> Tuple {
+    let mut result = [0.; 4];
+
+    for r in 0..4 {
+        let t = Tuple::new(lhs.get(r, 0), lhs.get(r, 1), lhs.get(r, 2), lhs.get(r, 3));
+
+        result[r] = t.dot(*rhs);
+    }
+
+    Tuple::new(result[0], result[1], result[2], result[3])
+}
+
 impl From<[f64; 16]> for M4 {
     fn from(data: [f64; 16]) -> Self {
-        M4 { data }
+        M4 {
+            data,
+            determinant: None,
+        }
+    }
+}
+
+impl From<M4> for [f64; 16] {
+    fn from(m: M4) -> Self {
+        m.data
     }
 }
 
 impl PartialEq for M4 {
     fn eq(&self, other: &Self) -> bool {
-        f64_eq(self.data[0], other.data[0])
-            && f64_eq(self.data[1], other.data[1])
-            && f64_eq(self.data[2], other.data[2])
-            && f64_eq(self.data[3], other.data[3])
-            && f64_eq(self.data[4], other.data[4])
-            && f64_eq(self.data[5], other.data[5])
-            && f64_eq(self.data[6], other.data[6])
-            && f64_eq(self.data[7], other.data[7])
-            && f64_eq(self.data[8], other.data[8])
-            && f64_eq(self.data[9], other.data[9])
-            && f64_eq(self.data[10], other.data[10])
-            && f64_eq(self.data[11], other.data[11])
-            && f64_eq(self.data[12], other.data[12])
-            && f64_eq(self.data[13], other.data[13])
-            && f64_eq(self.data[14], other.data[14])
-            && f64_eq(self.data[15], other.data[15])
+        self.approx_eq(other, EPSILON)
     }
 }
 
@@ -310,6 +399,26 @@ mod tests {
         assert_eq!(15.5, m.get(3, 2));
     }
 
+    #[test]
+    fn test_m4_approx_eq_with_looser_tolerance() {
+        let mut m = M4::identity();
+        m.set(1.0005, 1, 1);
+
+        assert_ne!(M4::identity(), m);
+        assert!(M4::identity().approx_eq(&m, 1e-3));
+    }
+
+    #[test]
+    fn test_rows_and_columns_of_m4_matrix() {
+        let m = M4::from([
+            1., 2., 3., 4., 5.5, 6.5, 7.5, 8.5, 9., 10., 11., 12., 13.5, 14.5, 15.5, 16.5,
+        ]);
+
+        assert_eq!([5.5, 6.5, 7.5, 8.5], m.row(1).as_array());
+        assert_eq!([3., 7.5, 11., 15.5], m.col(2).as_array());
+        assert_eq!(m, M4::from_rows(m.row(0), m.row(1), m.row(2), m.row(3)));
+    }
+
     #[test]
     fn test_create_and_access_m2_matrix() {
         let m = M2::from([-3., 5., 1., -2.]);
@@ -397,6 +506,27 @@ mod tests {
         assert_eq!(e, r);
     }
 
+    #[test]
+    fn test_borrowed_multiplication_equals_by_value_multiplication() {
+        let m1 = M4::from([
+            1., 2., 3., 4., 5., 6., 7., 8., 9., 8., 7., 6., 5., 4., 3., 2.,
+        ]);
+        let m2 = M4::from([
+            -2., 1., 2., 3., 3., 2., 1., -1., 4., 3., 6., 5., 1., 2., 7., 8.,
+        ]);
+        let p = Tuple::new(1., 2., 3., 1.);
+        let (borrowed_m1, borrowed_m2, borrowed_p) = (&m1, &m2, &p);
+
+        assert_eq!(m1 * m2, borrowed_m1 * borrowed_m2);
+        assert_eq!(m1 * p, borrowed_m1 * borrowed_p);
+        assert_eq!(m1 * p, borrowed_m1 * p);
+
+        let mut m = m1;
+        m *= m2;
+        assert_eq!(m1 * m2, m);
+        assert_eq!((m1 * m2).determinant(), m.determinant());
+    }
+
     #[test]
     fn test_multiply_matrix_by_identity_matrix() {
         let m = M4::from([
@@ -519,6 +649,7 @@ mod tests {
 
         assert_eq!(0., m.determinant());
         assert!(!m.is_invertible());
+        assert!(M4::from([f64::NAN; 16]).inverse().is_none());
     }
 
     #[test]
@@ -582,4 +713,34 @@ mod tests {
 
         assert_eq!(a, c * b.inverse().unwrap())
     }
+
+    #[test]
+    fn test_cached_determinant_follows_set() {
+        let mut m = M4::from([
+            -2., -8., 3., 5., -3., 1., 7., 3., 1., 2., -9., 6., -6., 7., 7., -9.,
+        ]);
+
+        assert_eq!(None, m.determinant);
+        assert_eq!(-4071., m.determinant());
+        assert_eq!(Some(1. / -4071.), m.inverse().unwrap().determinant);
+
+        // the first cofactor is 690, dropping the -2 in front of it adds 1380
+        m.set(0., 0, 0);
+        assert_eq!(-2691., m.determinant());
+        assert_eq!(M4::identity(), m * m.inverse().unwrap());
+    }
+
+    #[test]
+    fn test_two_inverses_of_the_same_matrix_are_equal() {
+        let m = M4::from([
+            -5., 2., 6., -8., 1., -5., 1., 8., 7., 7., -6., -7., 1., -3., 7., 4.,
+        ]);
+        let a = m.inverse().unwrap();
+        let b = m.inverse().unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a.determinant, b.determinant);
+        assert_eq!(m, a.inverse().unwrap());
+        assert!(f64_eq(1., (m * a).determinant()));
+    }
 }
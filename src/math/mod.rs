@@ -5,5 +5,10 @@ pub(crate) mod tuple;
 pub(crate) const EPSILON: f64 = 0.00001;
 
 pub(crate) fn f64_eq(a: f64, b: f64) -> bool {
-    (a - b).abs() < EPSILON
+    f64_eq_eps(a, b, EPSILON)
+}
+
+// for comparisons that need a tighter or looser tolerance than EPSILON
+pub(crate) fn f64_eq_eps(a: f64, b: f64, eps: f64) -> bool {
+    (a - b).abs() < eps
 }
@@ -49,6 +49,26 @@ pub(crate) fn rotation_z(radians: f64) -> M4 {
     base
 }
 
+// rotation around an axis through the origin, using Rodrigues' rotation formula
+pub(crate) fn rotation_axis(axis: Tuple, radians: f64) -> M4 {
+    let a = axis.normalize();
+    let (sin, cos) = radians.sin_cos();
+    let t = 1. - cos;
+
+    let mut base = M4::identity();
+    base.set(t * a.x * a.x + cos, 0, 0);
+    base.set(t * a.x * a.y - sin * a.z, 0, 1);
+    base.set(t * a.x * a.z + sin * a.y, 0, 2);
+    base.set(t * a.x * a.y + sin * a.z, 1, 0);
+    base.set(t * a.y * a.y + cos, 1, 1);
+    base.set(t * a.y * a.z - sin * a.x, 1, 2);
+    base.set(t * a.x * a.z - sin * a.y, 2, 0);
+    base.set(t * a.y * a.z + sin * a.x, 2, 1);
+    base.set(t * a.z * a.z + cos, 2, 2);
+
+    base
+}
+
 pub(crate) fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> M4 {
     let mut base = M4::identity();
     base.set(xy, 0, 1);
@@ -61,22 +81,45 @@ pub(crate) fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) ->
     base
 }
 
-pub(crate) fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> M4 {
-    let mut base = M4::identity();
+// fluent api of the book, every call applies its transformation after the previous ones, so
+// M4::transform().then_rotate_x(..).then_translate(..) equals translation(..) * rotation_x(..).
+// The helpers on ShapeProps and PatternProps multiply onto the right instead.
+impl M4 {
+    pub(crate) fn transform() -> M4 {
+        M4::identity()
+    }
+
+    pub(crate) fn then_translate(self, x: f64, y: f64, z: f64) -> M4 {
+        translation(x, y, z) * self
+    }
+
+    pub(crate) fn then_scale(self, x: f64, y: f64, z: f64) -> M4 {
+        scaling(x, y, z) * self
+    }
 
+    pub(crate) fn then_rotate_x(self, radians: f64) -> M4 {
+        rotation_x(radians) * self
+    }
+
+    pub(crate) fn then_rotate_y(self, radians: f64) -> M4 {
+        rotation_y(radians) * self
+    }
+
+    pub(crate) fn then_rotate_z(self, radians: f64) -> M4 {
+        rotation_z(radians) * self
+    }
+
+    pub(crate) fn then_shear(self, xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> M4 {
+        shearing(xy, xz, yx, yz, zx, zy) * self
+    }
+}
+
+pub(crate) fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> M4 {
     let forward_v = (to - from).normalize();
     let left_v = forward_v.cross(up.normalize());
     let true_up = left_v.cross(forward_v);
 
-    base.set(left_v.x, 0, 0);
-    base.set(left_v.y, 0, 1);
-    base.set(left_v.z, 0, 2);
-    base.set(true_up.x, 1, 0);
-    base.set(true_up.y, 1, 1);
-    base.set(true_up.z, 1, 2);
-    base.set(-forward_v.x, 2, 0);
-    base.set(-forward_v.y, 2, 1);
-    base.set(-forward_v.z, 2, 2);
+    let base = M4::from_rows(left_v, true_up, -forward_v, Tuple::new(0., 0., 0., 1.));
 
     base * translation(-from.x, -from.y, -from.z)
 }
@@ -84,6 +127,7 @@ pub(crate) fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> M4 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::f64_eq;
     use crate::math::tuple::Tuple;
     use std::f64::consts::PI;
 
@@ -293,6 +337,50 @@ mod tests {
         assert_eq!(transformation * p, Tuple::point(15., 0., 7.));
     }
 
+    #[test]
+    fn test_rotation_around_arbitrary_axis() {
+        assert_eq!(
+            rotation_x(PI / 2.),
+            rotation_axis(Tuple::direction(1., 0., 0.), PI / 2.)
+        );
+        assert_eq!(
+            rotation_z(PI / 3.),
+            rotation_axis(Tuple::direction(0., 0., 2.), PI / 3.)
+        );
+
+        let p = Tuple::point(0., 0., 1.);
+        let r = rotation_axis(Tuple::direction(0., 1., 0.), PI / 2.);
+        assert_eq!(rotation_y(PI / 2.) * p, r * p);
+        assert_eq!(Tuple::point(1., 0., 0.), r * p);
+
+        // a third turn around the diagonal cycles the axes
+        let r = rotation_axis(Tuple::direction(1., 1., 1.), 2. * PI / 3.);
+        assert_eq!(Tuple::point(0., 1., 0.), r * Tuple::point(1., 0., 0.));
+    }
+
+    #[test]
+    fn test_fluent_transformations_are_applied_in_call_order() {
+        let p = Tuple::point(1., 0., 1.);
+
+        let transformation = M4::transform()
+            .then_rotate_x(PI / 2.)
+            .then_scale(5., 5., 5.)
+            .then_translate(10., 5., 7.);
+
+        assert_eq!(transformation * p, Tuple::point(15., 0., 7.));
+        assert_eq!(
+            translation(10., 5., 7.) * scaling(5., 5., 5.) * rotation_x(PI / 2.),
+            transformation
+        );
+        assert_eq!(
+            rotation_z(0.3) * rotation_y(0.2) * shearing(1., 0., 0., 0., 0., 0.),
+            M4::transform()
+                .then_shear(1., 0., 0., 0., 0., 0.)
+                .then_rotate_y(0.2)
+                .then_rotate_z(0.3)
+        );
+    }
+
     #[test]
     fn test_transformation_matrix_for_default_orientation() {
         let from = Tuple::point(0., 0., 0.);
@@ -336,4 +424,17 @@ mod tests {
         let r = view_transform(from, to, up);
         assert_eq!(e, r);
     }
+
+    #[test]
+    fn test_view_transform_first_row_is_left_vector() {
+        let from = Tuple::point(1., 3., 2.);
+        let to = Tuple::point(4., -2., 8.);
+        let up = Tuple::direction(1., 1., 0.);
+
+        let left = view_transform(from, to, up).row(0).as_array();
+
+        for (expected, actual) in [-0.50709, 0.50709, 0.67612, -2.36643].iter().zip(&left) {
+            assert!(f64_eq(*expected, *actual));
+        }
+    }
 }
@@ -1,7 +1,8 @@
-use crate::math::f64_eq;
+use crate::math::{f64_eq_eps, EPSILON};
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct Tuple {
     pub(crate) x: f64,
     pub(crate) y: f64,
@@ -22,6 +23,18 @@ impl Tuple {
         Tuple::new(x, y, z, 0.)
     }
 
+    // like ==, but with the given tolerance, w still has to match exactly
+    pub(crate) fn approx_eq(&self, other: Tuple, eps: f64) -> bool {
+        f64_eq_eps(self.x, other.x, eps)
+            && f64_eq_eps(self.y, other.y, eps)
+            && f64_eq_eps(self.z, other.z, eps)
+            && self.w == other.w
+    }
+
+    pub(crate) fn as_array(&self) -> [f64; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
     pub(crate) fn is_point(&self) -> bool {
         return self.w == 1.;
     }
@@ -30,12 +43,20 @@ impl Tuple {
         return self.w == 0.;
     }
 
+    // length of the whole 4-tuple, only meaningful for directions (w = 0)
     pub(crate) fn magnitude(&self) -> f64 {
         let total = self.x.powi(2) + self.y.powi(2) + self.z.powi(2) + self.w.powi(2);
 
         total.sqrt()
     }
 
+    // length of x, y and z only, so points give their distance to the origin
+    pub(crate) fn magnitude3(&self) -> f64 {
+        let total = self.x.powi(2) + self.y.powi(2) + self.z.powi(2);
+
+        total.sqrt()
+    }
+
     pub(crate) fn normalize(&self) -> Tuple {
         let x = self.x / self.magnitude();
         let y = self.y / self.magnitude();
@@ -60,14 +81,73 @@ impl Tuple {
     pub(crate) fn reflect(&self, normal: Tuple) -> Tuple {
         *self - normal * 2. * self.dot(normal)
     }
+
+    // bends an incoming direction by snell's law, n_ratio is n1 / n2. None under total
+    // internal reflection.
+    pub(crate) fn refract(&self, normal: Tuple, n_ratio: f64) -> Option<Tuple> {
+        let cos_i = -self.dot(normal);
+        let sin2_t = n_ratio.powi(2) * (1. - cos_i.powi(2));
+
+        if sin2_t > 1. {
+            return None;
+        }
+
+        let cos_t = (1. - sin2_t).sqrt();
+
+        Some(normal * (n_ratio * cos_i - cos_t) + *self * n_ratio)
+    }
+
+    pub(crate) fn lerp(&self, other: Tuple, t: f64) -> Tuple {
+        *self + (other - *self) * t
+    }
+
+    // spherical interpolation between two directions, the result is always a unit vector
+    // and t moves along the arc at a constant angular velocity
+    pub(crate) fn slerp(&self, other: Tuple, t: f64) -> Tuple {
+        let from = self.normalize();
+        let to = other.normalize();
+        let cos = from.dot(to).clamp(-1., 1.);
+
+        // nearly parallel, the arc is too short for a stable division by sin
+        if cos > 1. - EPSILON {
+            return from.lerp(to, t).normalize();
+        }
+
+        // antiparallel, every great circle connects both, so pick any perpendicular one
+        if cos < -1. + EPSILON {
+            let axis = if from.x.abs() < 0.9 {
+                Tuple::direction(1., 0., 0.)
+            } else {
+                Tuple::direction(0., 1., 0.)
+            };
+            let perpendicular = from.cross(axis).normalize();
+            let angle = std::f64::consts::PI * t;
+
+            return from * angle.cos() + perpendicular * angle.sin();
+        }
+
+        let angle = cos.acos();
+
+        (from * ((1. - t) * angle).sin() + to * (t * angle).sin()) / angle.sin()
+    }
+}
+
+impl From<[f64; 4]> for Tuple {
+    fn from(data: [f64; 4]) -> Self {
+        Tuple::new(data[0], data[1], data[2], data[3])
+    }
+}
+
+// a bare triple has no w, it is taken as a point
+impl From<(f64, f64, f64)> for Tuple {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Tuple::point(x, y, z)
+    }
 }
 
 impl PartialEq for Tuple {
     fn eq(&self, other: &Self) -> bool {
-        f64_eq(self.x, other.x)
-            && f64_eq(self.y, other.y)
-            && f64_eq(self.z, other.z)
-            && self.w == other.w
+        self.approx_eq(*other, EPSILON)
     }
 }
 
@@ -139,6 +219,8 @@ impl Div<f64> for Tuple {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::f64_eq;
+    use std::f64::consts::FRAC_1_SQRT_2;
 
     #[test]
     fn test_tuple_with_w_equals_1_is_point() {
@@ -170,6 +252,40 @@ mod tests {
         assert_eq!(p, c);
     }
 
+    #[test]
+    fn test_approx_eq_with_looser_tolerance() {
+        let a = Tuple::point(1., 2., 3.);
+        let b = Tuple::point(1.0005, 2., 2.9995);
+
+        assert_ne!(a, b);
+        assert!(!a.approx_eq(b, EPSILON));
+        assert!(a.approx_eq(b, 1e-3));
+        assert!(!a.approx_eq(Tuple::direction(1., 2., 3.), 1e-3));
+    }
+
+    #[test]
+    fn test_tuple_from_array() {
+        let t = Tuple::from([1., 2., 3., 1.]);
+
+        assert!(t.is_point());
+        assert_eq!(Tuple::point(1., 2., 3.), t);
+    }
+
+    #[test]
+    fn test_tuple_from_triple_is_point() {
+        let t: Tuple = (1., 2., 3.).into();
+
+        assert_eq!(Tuple::point(1., 2., 3.), t);
+    }
+
+    #[test]
+    fn test_as_array_round_trips() {
+        let t = Tuple::direction(4.3, -4.2, 3.1);
+
+        assert_eq!([4.3, -4.2, 3.1, 0.], t.as_array());
+        assert_eq!(t, Tuple::from(t.as_array()));
+    }
+
     #[test]
     fn test_direction_has_factory_fn() {
         let p: Tuple = Tuple::direction(4.3, -4.2, 3.1);
@@ -312,6 +428,13 @@ mod tests {
         assert_eq!(e, r);
     }
 
+    #[test]
+    fn test_magnitude3_ignores_w() {
+        assert_eq!(5., Tuple::point(3., 4., 0.).magnitude3());
+        assert_eq!(5., Tuple::direction(3., 4., 0.).magnitude3());
+        assert_eq!(26f64.sqrt(), Tuple::point(3., 4., 0.).magnitude());
+    }
+
     #[test]
     fn test_normalize_1() {
         let d = Tuple::direction(4., 0., 0.);
@@ -393,4 +516,56 @@ mod tests {
         let e = Tuple::direction(1., 0., 0.);
         assert_eq!(e, d.reflect(n))
     }
+
+    #[test]
+    fn test_refracting_direction_entering_glass_at_45_degree() {
+        let d = Tuple::direction(1., -1., 0.).normalize();
+        let n = Tuple::direction(0., 1., 0.);
+
+        let sin_t = FRAC_1_SQRT_2 / 1.5;
+        let e = Tuple::direction(sin_t, -(1. - sin_t * sin_t).sqrt(), 0.);
+        assert_eq!(Some(e), d.refract(n, 1. / 1.5));
+    }
+
+    #[test]
+    fn test_refracting_under_total_internal_reflection() {
+        let d = Tuple::direction(1., -1., 0.).normalize();
+        let n = Tuple::direction(0., 1., 0.);
+
+        assert_eq!(None, d.refract(n, 1.5));
+    }
+
+    #[test]
+    fn test_lerp_between_tuples() {
+        let a = Tuple::point(1., 2., 3.);
+        let b = Tuple::point(3., -2., 4.);
+
+        assert_eq!(a, a.lerp(b, 0.));
+        assert_eq!(b, a.lerp(b, 1.));
+        assert_eq!(Tuple::point(2., 0., 3.5), a.lerp(b, 0.5));
+    }
+
+    #[test]
+    fn test_slerp_between_directions() {
+        let a = Tuple::direction(1., 0., 0.);
+        let b = Tuple::direction(0., 1., 0.);
+
+        let r = a.slerp(b, 0.5);
+
+        assert!(f64_eq(1., r.magnitude()));
+        assert_eq!(Tuple::direction(2f64.sqrt() / 2., 2f64.sqrt() / 2., 0.), r);
+        assert_eq!(a, a.slerp(b, 0.));
+        assert_eq!(b, a.slerp(b * 3., 1.));
+    }
+
+    #[test]
+    fn test_slerp_between_parallel_and_antiparallel_directions() {
+        let a = Tuple::direction(0., 0., 1.);
+
+        assert_eq!(a, a.slerp(a, 0.3));
+
+        let r = a.slerp(-a, 0.5);
+        assert!(f64_eq(1., r.magnitude()));
+        assert!(f64_eq(0., r.dot(a)));
+    }
 }
@@ -0,0 +1,87 @@
+use crate::math::tuple::Tuple;
+use crate::pattern::{Pattern, PatternProps};
+use crate::scene::shading::color::Color;
+
+#[derive(Clone, Debug)]
+pub(crate) struct BlendedPattern {
+    props: PatternProps,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl BlendedPattern {
+    pub(crate) fn new(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> BlendedPattern {
+        BlendedPattern {
+            props: PatternProps::default(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for BlendedPattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let a = self.a.pattern_at_object(point);
+        let b = self.b.pattern_at_object(point);
+
+        (a + b) * 0.5
+    }
+
+    fn get_props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{rotation_y, scaling};
+    use crate::pattern::stripe::StripePattern;
+    use crate::primitives::shape::Shape;
+    use crate::primitives::sphere::Sphere;
+    use std::f64::consts::PI;
+
+    fn perpendicular_stripes() -> BlendedPattern {
+        let a = StripePattern::new(Color::white(), Color::black());
+        let mut b = StripePattern::new(Color::white(), Color::black());
+        b.mut_props().set_transform(rotation_y(PI / 2.));
+
+        BlendedPattern::new(Box::new(a), Box::new(b))
+    }
+
+    #[test]
+    fn test_blended_pattern_averages_both_patterns() {
+        let pattern = perpendicular_stripes();
+        let gray = Color::new(0.5, 0.5, 0.5);
+
+        assert_eq!(
+            Color::white(),
+            pattern.pattern_at(Tuple::point(0.5, 0., -0.5))
+        );
+        assert_eq!(gray, pattern.pattern_at(Tuple::point(0.5, 0., 0.5)));
+        assert_eq!(gray, pattern.pattern_at(Tuple::point(1.5, 0., -0.5)));
+        assert_eq!(
+            Color::black(),
+            pattern.pattern_at(Tuple::point(1.5, 0., 0.5))
+        );
+    }
+
+    #[test]
+    fn test_blended_pattern_with_object_transformation() {
+        let mut shape = Sphere::new();
+        shape.mut_props().set_transform(scaling(2., 2., 2.));
+        let pattern = perpendicular_stripes();
+
+        let c = pattern.pattern_at_shape(&shape, Tuple::point(1., 0., 1.));
+
+        assert_eq!(Color::new(0.5, 0.5, 0.5), c);
+    }
+}
@@ -2,7 +2,7 @@ use crate::math::tuple::Tuple;
 use crate::pattern::{Pattern, PatternProps};
 use crate::scene::shading::color::Color;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct CheckerPattern {
     props: PatternProps,
     a: Color,
@@ -20,6 +20,10 @@ impl CheckerPattern {
 }
 
 impl Pattern for CheckerPattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
     fn pattern_at(&self, point: Tuple) -> Color {
         if (point.x.floor() + point.y.floor() + point.z.floor()) % 2. == 0. {
             self.a
@@ -0,0 +1,86 @@
+use crate::math::tuple::Tuple;
+use crate::pattern::{Pattern, PatternProps};
+use crate::scene::shading::color::Color;
+
+#[derive(Clone, Debug)]
+pub(crate) struct CheckeredPatterns {
+    props: PatternProps,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl CheckeredPatterns {
+    pub(crate) fn new(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> CheckeredPatterns {
+        CheckeredPatterns {
+            props: PatternProps::default(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for CheckeredPatterns {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
+    fn pattern_at(&self, point: Tuple) -> Color {
+        if (point.x.floor() + point.y.floor() + point.z.floor()) % 2. == 0. {
+            self.a.pattern_at_object(point)
+        } else {
+            self.b.pattern_at_object(point)
+        }
+    }
+
+    fn get_props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::checker::CheckerPattern;
+    use crate::pattern::gradient::GradientPattern;
+    use crate::pattern::stripe::StripePattern;
+
+    #[test]
+    fn test_solid_sub_patterns_match_checker_pattern() {
+        let white = StripePattern::new(Color::white(), Color::white());
+        let black = StripePattern::new(Color::black(), Color::black());
+        let pattern = CheckeredPatterns::new(Box::new(white), Box::new(black));
+        let checker = CheckerPattern::new(Color::white(), Color::black());
+
+        for point in &[
+            Tuple::point(0., 0., 0.),
+            Tuple::point(0.99, 0., 0.),
+            Tuple::point(1.01, 0., 0.),
+            Tuple::point(0., 1.01, 0.),
+            Tuple::point(0., 0., 1.01),
+            Tuple::point(1.5, 1.5, 0.5),
+            Tuple::point(-0.5, 0., 0.),
+        ] {
+            assert_eq!(checker.pattern_at(*point), pattern.pattern_at(*point));
+        }
+    }
+
+    #[test]
+    fn test_checkered_patterns_delegate_to_sub_pattern() {
+        let stripes = StripePattern::new(Color::white(), Color::black());
+        let gradient = GradientPattern::new(Color::white(), Color::black());
+        let pattern = CheckeredPatterns::new(Box::new(stripes), Box::new(gradient));
+
+        assert_eq!(
+            Color::white(),
+            pattern.pattern_at(Tuple::point(0.5, 0., 0.5))
+        );
+        assert_eq!(
+            Color::new(0.5, 0.5, 0.5),
+            pattern.pattern_at(Tuple::point(1.5, 0., 0.5))
+        );
+    }
+}
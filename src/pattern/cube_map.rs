@@ -0,0 +1,213 @@
+use crate::math::tuple::Tuple;
+use crate::pattern::uv_checker::UvCheckers;
+use crate::pattern::{Pattern, PatternProps};
+use crate::scene::shading::color::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+// wraps a uv pattern around each face of the unit cube, like a skybox
+#[derive(Clone, Debug)]
+pub(crate) struct CubeMap {
+    props: PatternProps,
+    left: UvCheckers,
+    front: UvCheckers,
+    right: UvCheckers,
+    back: UvCheckers,
+    up: UvCheckers,
+    down: UvCheckers,
+}
+
+impl CubeMap {
+    pub(crate) fn new(
+        left: UvCheckers,
+        front: UvCheckers,
+        right: UvCheckers,
+        back: UvCheckers,
+        up: UvCheckers,
+        down: UvCheckers,
+    ) -> CubeMap {
+        CubeMap {
+            props: PatternProps::default(),
+            left,
+            front,
+            right,
+            back,
+            up,
+            down,
+        }
+    }
+}
+
+impl Pattern for CubeMap {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let (face, (u, v)) = match face_from_point(point) {
+            CubeFace::Left => (&self.left, cube_uv_left(point)),
+            CubeFace::Right => (&self.right, cube_uv_right(point)),
+            CubeFace::Front => (&self.front, cube_uv_front(point)),
+            CubeFace::Back => (&self.back, cube_uv_back(point)),
+            CubeFace::Up => (&self.up, cube_uv_up(point)),
+            CubeFace::Down => (&self.down, cube_uv_down(point)),
+        };
+
+        face.uv_pattern_at(u, v)
+    }
+
+    fn get_props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+}
+
+// the face is picked by the component with the largest magnitude
+pub(crate) fn face_from_point(point: Tuple) -> CubeFace {
+    let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+    if coord == point.x {
+        CubeFace::Right
+    } else if coord == -point.x {
+        CubeFace::Left
+    } else if coord == point.y {
+        CubeFace::Up
+    } else if coord == -point.y {
+        CubeFace::Down
+    } else if coord == point.z {
+        CubeFace::Front
+    } else {
+        CubeFace::Back
+    }
+}
+
+// maps a coordinate in [-1, 1] to [0, 1), in either direction
+fn face_coord(value: f64) -> f64 {
+    value.rem_euclid(2.) / 2.
+}
+
+pub(crate) fn cube_uv_front(point: Tuple) -> (f64, f64) {
+    (face_coord(point.x + 1.), face_coord(point.y + 1.))
+}
+
+pub(crate) fn cube_uv_back(point: Tuple) -> (f64, f64) {
+    (face_coord(1. - point.x), face_coord(point.y + 1.))
+}
+
+pub(crate) fn cube_uv_left(point: Tuple) -> (f64, f64) {
+    (face_coord(point.z + 1.), face_coord(point.y + 1.))
+}
+
+pub(crate) fn cube_uv_right(point: Tuple) -> (f64, f64) {
+    (face_coord(1. - point.z), face_coord(point.y + 1.))
+}
+
+pub(crate) fn cube_uv_up(point: Tuple) -> (f64, f64) {
+    (face_coord(point.x + 1.), face_coord(1. - point.z))
+}
+
+pub(crate) fn cube_uv_down(point: Tuple) -> (f64, f64) {
+    (face_coord(point.x + 1.), face_coord(point.z + 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{scaling, translation};
+    use crate::primitives::cube::Cube;
+    use crate::primitives::shape::Shape;
+
+    #[test]
+    fn test_identifying_the_face_of_a_cube_from_a_point() {
+        assert_eq!(
+            CubeFace::Left,
+            face_from_point(Tuple::point(-1., 0.5, -0.25))
+        );
+        assert_eq!(
+            CubeFace::Right,
+            face_from_point(Tuple::point(1.1, -0.75, 0.8))
+        );
+        assert_eq!(
+            CubeFace::Front,
+            face_from_point(Tuple::point(0.1, 0.6, 0.9))
+        );
+        assert_eq!(CubeFace::Back, face_from_point(Tuple::point(-0.7, 0., -2.)));
+        assert_eq!(CubeFace::Up, face_from_point(Tuple::point(0.5, 1., 0.9)));
+        assert_eq!(
+            CubeFace::Down,
+            face_from_point(Tuple::point(-0.2, -1.3, 1.1))
+        );
+    }
+
+    #[test]
+    fn test_uv_mapping_the_faces_of_a_cube() {
+        assert_eq!((0.25, 0.75), cube_uv_front(Tuple::point(-0.5, 0.5, 1.)));
+        assert_eq!((0.75, 0.25), cube_uv_front(Tuple::point(0.5, -0.5, 1.)));
+        assert_eq!((0.25, 0.75), cube_uv_back(Tuple::point(0.5, 0.5, -1.)));
+        assert_eq!((0.75, 0.25), cube_uv_back(Tuple::point(-0.5, -0.5, -1.)));
+        assert_eq!((0.25, 0.75), cube_uv_left(Tuple::point(-1., 0.5, -0.5)));
+        assert_eq!((0.75, 0.25), cube_uv_left(Tuple::point(-1., -0.5, 0.5)));
+        assert_eq!((0.25, 0.75), cube_uv_right(Tuple::point(1., 0.5, 0.5)));
+        assert_eq!((0.75, 0.25), cube_uv_right(Tuple::point(1., -0.5, -0.5)));
+        assert_eq!((0.25, 0.75), cube_uv_up(Tuple::point(-0.5, 1., -0.5)));
+        assert_eq!((0.75, 0.25), cube_uv_up(Tuple::point(0.5, 1., 0.5)));
+        assert_eq!((0.25, 0.75), cube_uv_down(Tuple::point(-0.5, -1., 0.5)));
+        assert_eq!((0.75, 0.25), cube_uv_down(Tuple::point(0.5, -1., -0.5)));
+    }
+
+    #[test]
+    fn test_cube_map_dispatches_to_the_face_pattern() {
+        let solid = |c: Color| UvCheckers::new(1., 1., c, c);
+        let red = Color::new(1., 0., 0.);
+        let yellow = Color::new(1., 1., 0.);
+        let brown = Color::new(1., 0.5, 0.);
+        let green = Color::new(0., 1., 0.);
+        let cyan = Color::new(0., 1., 1.);
+        let blue = Color::new(0., 0., 1.);
+        let pattern = CubeMap::new(
+            solid(red),
+            solid(yellow),
+            solid(brown),
+            solid(green),
+            solid(cyan),
+            solid(blue),
+        );
+
+        assert_eq!(red, pattern.pattern_at(Tuple::point(-1., 0., 0.)));
+        assert_eq!(yellow, pattern.pattern_at(Tuple::point(0., 0., 1.)));
+        assert_eq!(brown, pattern.pattern_at(Tuple::point(1., 0., 0.)));
+        assert_eq!(green, pattern.pattern_at(Tuple::point(0., 0., -1.)));
+        assert_eq!(cyan, pattern.pattern_at(Tuple::point(0., 1., 0.)));
+        assert_eq!(blue, pattern.pattern_at(Tuple::point(0., -1., 0.)));
+    }
+
+    #[test]
+    fn test_cube_with_cube_map_colors_each_face() {
+        let white = UvCheckers::new(1., 1., Color::white(), Color::white());
+        let black = UvCheckers::new(1., 1., Color::black(), Color::black());
+        let pattern = CubeMap::new(black, white, black, black, black, black);
+
+        let mut cube = Cube::new();
+        cube.mut_props()
+            .set_transform(translation(0., 0., 5.) * scaling(2., 2., 2.));
+        cube.mut_props().set_pattern(Box::new(pattern));
+        let material = cube.get_props().get_material();
+
+        let front = material.surface_color(&cube, Tuple::point(0.5, 1.5, 7.));
+        let top = material.surface_color(&cube, Tuple::point(0.5, 2., 5.5));
+
+        assert_eq!(Color::white(), front);
+        assert_eq!(Color::black(), top);
+    }
+}
@@ -2,11 +2,12 @@ use crate::math::tuple::Tuple;
 use crate::pattern::{Pattern, PatternProps};
 use crate::scene::shading::color::Color;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct GradientPattern {
     props: PatternProps,
     a: Color,
     b: Color,
+    clamp: bool,
 }
 
 impl GradientPattern {
@@ -15,16 +16,29 @@ impl GradientPattern {
             props: PatternProps::default(),
             a,
             b,
+            clamp: false,
         }
     }
+
+    // the gradient restarts every unit, clamped it stays at a and b outside of [0, 1]
+    pub(crate) fn set_clamp(&mut self, clamp: bool) {
+        self.clamp = clamp;
+    }
 }
 
 impl Pattern for GradientPattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
     fn pattern_at(&self, point: Tuple) -> Color {
-        let distance = self.b - self.a;
-        let fraction = point.x - point.x.floor();
+        let fraction = if self.clamp {
+            point.x.clamp(0., 1.)
+        } else {
+            point.x - point.x.floor()
+        };
 
-        self.a + distance * fraction
+        self.a.lerp(self.b, fraction)
     }
 
     fn get_props(&self) -> &PatternProps {
@@ -63,4 +77,33 @@ mod tests {
             Color::new(0.25, 0.25, 0.25)
         );
     }
+
+    #[test]
+    fn test_clamped_gradient_stays_at_its_colors_outside_of_unit_range() {
+        let mut pattern = GradientPattern::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.));
+        pattern.set_clamp(true);
+
+        assert_eq!(
+            pattern.pattern_at(Tuple::point(-0.5, 0., 0.)),
+            Color::new(1., 1., 1.)
+        );
+        assert_eq!(
+            pattern.pattern_at(Tuple::point(1.5, 0., 0.)),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_gradient_wraps_every_unit_by_default() {
+        let pattern = GradientPattern::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.));
+
+        assert_eq!(
+            pattern.pattern_at(Tuple::point(1.5, 0., 0.)),
+            pattern.pattern_at(Tuple::point(0.5, 0., 0.))
+        );
+        assert_eq!(
+            pattern.pattern_at(Tuple::point(-0.75, 0., 0.)),
+            Color::new(0.75, 0.75, 0.75)
+        );
+    }
 }
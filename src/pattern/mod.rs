@@ -1,17 +1,34 @@
 use crate::math::matrix::M4;
+use crate::math::transformation::{rotation_x, rotation_y, rotation_z, scaling, translation};
 use crate::math::tuple::Tuple;
 use crate::primitives::shape::Shape;
 use crate::scene::shading::color::Color;
 use std::fmt::Debug;
 
+pub(crate) mod blended;
 pub(crate) mod checker;
+pub(crate) mod checkered;
+pub(crate) mod cube_map;
 pub(crate) mod gradient;
 pub(crate) mod ring;
 pub(crate) mod stripe;
+pub(crate) mod texture_map;
+pub(crate) mod uv_checker;
+pub(crate) mod uv_map;
 
 pub(crate) trait Pattern: Debug + Sync + Send {
+    // boxed copy, so materials holding a pattern can be cloned
+    fn clone_box(&self) -> Box<dyn Pattern>;
+
     fn pattern_at_shape(&self, shape: &dyn Shape, point: Tuple) -> Color {
-        let object_point = shape.get_props().get_transform().inverse().unwrap() * point;
+        let object_point = shape.get_props().get_inverse_transform() * point;
+
+        self.pattern_at_object(object_point)
+    }
+
+    // applies the pattern transformation to a point in object space, so patterns can
+    // delegate to nested patterns without knowing about the shape
+    fn pattern_at_object(&self, object_point: Tuple) -> Color {
         let pattern_point = self.get_props().get_transform().inverse().unwrap() * object_point;
 
         self.pattern_at(pattern_point)
@@ -22,6 +39,21 @@ pub(crate) trait Pattern: Debug + Sync + Send {
     fn get_props(&self) -> &PatternProps;
 
     fn mut_props(&mut self) -> &mut PatternProps;
+
+    // consuming builder, so a pattern can be transformed where it is created
+    fn with_transform(mut self, transform: M4) -> Self
+    where
+        Self: Sized,
+    {
+        self.mut_props().set_transform(transform);
+        self
+    }
+}
+
+impl Clone for Box<dyn Pattern> {
+    fn clone(&self) -> Box<dyn Pattern> {
+        self.clone_box()
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -43,15 +75,43 @@ impl PatternProps {
     pub(crate) fn set_transform(&mut self, new: M4) {
         self.transform = new
     }
+
+    // fluent helpers multiplied onto the right of the current transformation like on
+    // ShapeProps, unlike M4's then_* helpers which apply after it
+    pub(crate) fn translate(&mut self, x: f64, y: f64, z: f64) -> &mut PatternProps {
+        self.set_transform(self.transform * translation(x, y, z));
+        self
+    }
+
+    pub(crate) fn scale(&mut self, x: f64, y: f64, z: f64) -> &mut PatternProps {
+        self.set_transform(self.transform * scaling(x, y, z));
+        self
+    }
+
+    pub(crate) fn rotate_x(&mut self, radians: f64) -> &mut PatternProps {
+        self.set_transform(self.transform * rotation_x(radians));
+        self
+    }
+
+    pub(crate) fn rotate_y(&mut self, radians: f64) -> &mut PatternProps {
+        self.set_transform(self.transform * rotation_y(radians));
+        self
+    }
+
+    pub(crate) fn rotate_z(&mut self, radians: f64) -> &mut PatternProps {
+        self.set_transform(self.transform * rotation_z(radians));
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::math::transformation::{scaling, translation};
+    use crate::pattern::stripe::StripePattern;
     use crate::primitives::shape::Shape;
     use crate::primitives::sphere::Sphere;
     use crate::scene::shading::color::Color;
+    use std::f64::consts::PI;
 
     #[derive(Copy, Clone, Debug)]
     struct TestPattern {
@@ -59,6 +119,10 @@ mod tests {
     }
 
     impl Pattern for TestPattern {
+        fn clone_box(&self) -> Box<dyn Pattern> {
+            Box::new(*self)
+        }
+
         fn pattern_at(&self, point: Tuple) -> Color {
             Color::new(point.x, point.y, point.z)
         }
@@ -93,6 +157,29 @@ mod tests {
         assert_eq!(pattern.get_props().get_transform(), translation(1., 2., 3.))
     }
 
+    #[test]
+    fn test_pattern_with_transform_at_construction() {
+        let pattern =
+            StripePattern::new(Color::white(), Color::black()).with_transform(scaling(2., 2., 2.));
+
+        assert_eq!(scaling(2., 2., 2.), pattern.get_props().get_transform());
+    }
+
+    #[test]
+    fn test_fluent_pattern_transform_helpers_post_multiply() {
+        let mut pattern = test_pattern();
+        pattern
+            .mut_props()
+            .translate(1., 0., 0.)
+            .scale(2., 2., 2.)
+            .rotate_y(PI / 2.);
+
+        assert_eq!(
+            translation(1., 0., 0.) * scaling(2., 2., 2.) * rotation_y(PI / 2.),
+            pattern.get_props().get_transform()
+        );
+    }
+
     #[test]
     fn test_pattern_with_object_transformation() {
         let mut shape = Sphere::new();
@@ -3,7 +3,7 @@ use crate::math::tuple::Tuple;
 use crate::pattern::{Pattern, PatternProps};
 use crate::scene::shading::color::Color;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct RingPattern {
     props: PatternProps,
     a: Color,
@@ -21,8 +21,14 @@ impl RingPattern {
 }
 
 impl Pattern for RingPattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
     fn pattern_at(&self, point: Tuple) -> Color {
-        if f64_eq(point.x.powi(2) + point.z.powi(2).sqrt().floor() % 2., 0.) {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt();
+
+        if f64_eq(distance.floor() % 2., 0.) {
             self.a
         } else {
             self.b
@@ -41,7 +47,9 @@ impl Pattern for RingPattern {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::transformation::scaling;
     use crate::math::tuple::Tuple;
+    use crate::primitives::sphere::Sphere;
     use crate::scene::shading::color::Color;
 
     #[test]
@@ -65,4 +73,39 @@ mod tests {
             pattern.pattern_at(Tuple::point(0.708, 0., 0.708))
         );
     }
+
+    #[test]
+    fn test_ring_alternates_with_distance_from_center() {
+        let pattern = RingPattern::new(Color::white(), Color::black());
+
+        assert_eq!(
+            Color::black(),
+            pattern.pattern_at(Tuple::point(1.5, 0., 0.))
+        );
+        assert_eq!(
+            Color::white(),
+            pattern.pattern_at(Tuple::point(2.1, 0., 0.))
+        );
+        assert_eq!(
+            Color::white(),
+            pattern.pattern_at(Tuple::point(1.5, 0., 1.5))
+        );
+        assert_eq!(Color::white(), pattern.pattern_at(Tuple::point(0., 5., 0.)));
+    }
+
+    #[test]
+    fn test_ring_with_pattern_transformation() {
+        let shape = Sphere::new();
+        let mut pattern = RingPattern::new(Color::white(), Color::black());
+        pattern.mut_props().set_transform(scaling(2., 2., 2.));
+
+        assert_eq!(
+            Color::white(),
+            pattern.pattern_at_shape(&shape, Tuple::point(1.5, 0., 0.))
+        );
+        assert_eq!(
+            Color::black(),
+            pattern.pattern_at_shape(&shape, Tuple::point(2.5, 0., 0.))
+        );
+    }
 }
@@ -2,7 +2,7 @@ use crate::math::tuple::Tuple;
 use crate::pattern::{Pattern, PatternProps};
 use crate::scene::shading::color::Color;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct StripePattern {
     a: Color,
     b: Color,
@@ -10,6 +10,10 @@ pub(crate) struct StripePattern {
 }
 
 impl Pattern for StripePattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
     fn pattern_at(&self, point: Tuple) -> Color {
         if point.x.floor() % 2. == 0. {
             self.a
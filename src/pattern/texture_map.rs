@@ -0,0 +1,64 @@
+use crate::math::tuple::Tuple;
+use crate::pattern::uv_checker::UvCheckers;
+use crate::pattern::{Pattern, PatternProps};
+use crate::scene::shading::color::Color;
+
+// applies a 2D uv pattern to a surface by mapping each 3D point to (u, v)
+#[derive(Clone, Debug)]
+pub(crate) struct TextureMapPattern {
+    props: PatternProps,
+    uv_pattern: UvCheckers,
+    uv_map: fn(Tuple) -> (f64, f64),
+}
+
+impl TextureMapPattern {
+    pub(crate) fn new(
+        uv_pattern: UvCheckers,
+        uv_map: fn(Tuple) -> (f64, f64),
+    ) -> TextureMapPattern {
+        TextureMapPattern {
+            props: PatternProps::default(),
+            uv_pattern,
+            uv_map,
+        }
+    }
+}
+
+impl Pattern for TextureMapPattern {
+    fn clone_box(&self) -> Box<dyn Pattern> {
+        Box::new(self.clone())
+    }
+
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let (u, v) = (self.uv_map)(point);
+
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+
+    fn get_props(&self) -> &PatternProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut PatternProps {
+        &mut self.props
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::uv_map::planar_map;
+
+    #[test]
+    fn test_planar_uv_checkers_on_a_plane() {
+        let black = Color::new(0., 0., 0.);
+        let white = Color::new(1., 1., 1.);
+        let checkers = UvCheckers::new(2., 2., black, white);
+        let pattern = TextureMapPattern::new(checkers, planar_map);
+
+        assert_eq!(black, pattern.pattern_at(Tuple::point(0.25, 0., 0.25)));
+        assert_eq!(white, pattern.pattern_at(Tuple::point(0.75, 0., 0.25)));
+        assert_eq!(white, pattern.pattern_at(Tuple::point(0.25, 0., 0.75)));
+        assert_eq!(black, pattern.pattern_at(Tuple::point(-0.25, 0., -0.25)));
+    }
+}
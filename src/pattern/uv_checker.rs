@@ -0,0 +1,50 @@
+use crate::scene::shading::color::Color;
+
+// a checker pattern in 2D texture space, where u and v are expected in [0, 1)
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct UvCheckers {
+    width: f64,
+    height: f64,
+    a: Color,
+    b: Color,
+}
+
+impl UvCheckers {
+    pub(crate) fn new(width: f64, height: f64, a: Color, b: Color) -> UvCheckers {
+        UvCheckers {
+            width,
+            height,
+            a,
+            b,
+        }
+    }
+
+    pub(crate) fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+
+        if (u2 + v2) % 2. == 0. {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checker_pattern_in_2d() {
+        let black = Color::new(0., 0., 0.);
+        let white = Color::new(1., 1., 1.);
+        let checkers = UvCheckers::new(2., 2., black, white);
+
+        assert_eq!(black, checkers.uv_pattern_at(0., 0.));
+        assert_eq!(white, checkers.uv_pattern_at(0.5, 0.));
+        assert_eq!(white, checkers.uv_pattern_at(0., 0.5));
+        assert_eq!(black, checkers.uv_pattern_at(0.5, 0.5));
+        assert_eq!(black, checkers.uv_pattern_at(1., 1.));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::math::tuple::Tuple;
+use std::f64::consts::PI;
+
+// projects a point onto the xz plane, repeating the texture every unit
+pub(crate) fn planar_map(point: Tuple) -> (f64, f64) {
+    (point.x.rem_euclid(1.), point.z.rem_euclid(1.))
+}
+
+// wraps the texture around the y axis, u runs counter-clockwise seen from above and
+// starts at -z, v repeats every unit of height
+pub(crate) fn cylindrical_map(point: Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2. * PI);
+    let u = 1. - (raw_u + 0.5);
+
+    (u, point.y.rem_euclid(1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::f64_eq;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    fn assert_uv(expected: (f64, f64), actual: (f64, f64)) {
+        assert!(
+            f64_eq(expected.0, actual.0) && f64_eq(expected.1, actual.1),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_planar_mapping_on_3d_point() {
+        assert_uv((0.25, 0.5), planar_map(Tuple::point(0.25, 0., 0.5)));
+        assert_uv((0.25, 0.75), planar_map(Tuple::point(0.25, 0., -0.25)));
+        assert_uv((0.25, 0.75), planar_map(Tuple::point(0.25, 0.5, -0.25)));
+        assert_uv((0.25, 0.5), planar_map(Tuple::point(1.25, 0., 0.5)));
+        assert_uv((0.25, 0.25), planar_map(Tuple::point(0.25, 0., -1.75)));
+        assert_uv((0., 0.), planar_map(Tuple::point(1., 0., -1.)));
+        assert_uv((0., 0.), planar_map(Tuple::point(0., 0., 0.)));
+    }
+
+    #[test]
+    fn test_cylindrical_mapping_on_3d_point() {
+        assert_uv((0., 0.), cylindrical_map(Tuple::point(0., 0., -1.)));
+        assert_uv((0., 0.5), cylindrical_map(Tuple::point(0., 0.5, -1.)));
+        assert_uv((0., 0.), cylindrical_map(Tuple::point(0., 1., -1.)));
+        assert_uv(
+            (0.125, 0.5),
+            cylindrical_map(Tuple::point(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2)),
+        );
+        assert_uv((0.25, 0.5), cylindrical_map(Tuple::point(1., 0.5, 0.)));
+        assert_uv(
+            (0.375, 0.5),
+            cylindrical_map(Tuple::point(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2)),
+        );
+        assert_uv((0.5, 0.25), cylindrical_map(Tuple::point(0., 0.25, 1.)));
+        assert_uv(
+            (0.625, 0.5),
+            cylindrical_map(Tuple::point(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2)),
+        );
+        assert_uv((0.75, 0.25), cylindrical_map(Tuple::point(-1., 1.25, 0.)));
+        assert_uv(
+            (0.875, 0.5),
+            cylindrical_map(Tuple::point(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2)),
+        );
+    }
+
+    #[test]
+    fn test_cylindrical_mapping_wraps_negative_heights() {
+        assert_uv((0.25, 0.75), cylindrical_map(Tuple::point(1., -0.25, 0.)));
+        assert_uv((0.25, 0.5), cylindrical_map(Tuple::point(1., -2.5, 0.)));
+    }
+}
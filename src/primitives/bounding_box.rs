@@ -0,0 +1,229 @@
+use crate::math::matrix::M4;
+use crate::math::tuple::Tuple;
+use crate::math::EPSILON;
+use crate::scene::tracing::ray::Ray;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct BoundingBox {
+    min: Tuple,
+    max: Tuple,
+}
+
+impl BoundingBox {
+    pub(crate) fn new(min: Tuple, max: Tuple) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    // contains nothing, adding a point or merging a box results in exactly that point/box
+    pub(crate) fn empty() -> BoundingBox {
+        BoundingBox::new(
+            Tuple::point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Tuple::point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    pub(crate) fn infinite() -> BoundingBox {
+        BoundingBox::new(
+            Tuple::point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Tuple::point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub(crate) fn get_min(&self) -> Tuple {
+        self.min
+    }
+
+    pub(crate) fn get_max(&self) -> Tuple {
+        self.max
+    }
+
+    pub(crate) fn is_finite(&self) -> bool {
+        (0..3)
+            .all(|axis| axis_of(self.min, axis).is_finite() && axis_of(self.max, axis).is_finite())
+    }
+
+    pub(crate) fn add_point(&mut self, point: Tuple) {
+        self.min = Tuple::point(
+            self.min.x.min(point.x),
+            self.min.y.min(point.y),
+            self.min.z.min(point.z),
+        );
+        self.max = Tuple::point(
+            self.max.x.max(point.x),
+            self.max.y.max(point.y),
+            self.max.z.max(point.z),
+        );
+    }
+
+    pub(crate) fn merge(&mut self, other: BoundingBox) {
+        self.add_point(other.min);
+        self.add_point(other.max);
+    }
+
+    pub(crate) fn centroid(&self) -> Tuple {
+        Tuple::point(
+            (self.min.x + self.max.x) / 2.,
+            (self.min.y + self.max.y) / 2.,
+            (self.min.z + self.max.z) / 2.,
+        )
+    }
+
+    // 0, 1 or 2 for the x, y or z axis
+    pub(crate) fn longest_axis(&self) -> usize {
+        let size = self.max - self.min;
+
+        if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    // box around all eight transformed corners. An infinite box stays infinite, because
+    // the matrix multiplication would mix infinities and zeros to NaN.
+    pub(crate) fn transform(&self, m: M4) -> BoundingBox {
+        if !self.is_finite() {
+            return BoundingBox::infinite();
+        }
+
+        let mut result = BoundingBox::empty();
+        for &x in &[self.min.x, self.max.x] {
+            for &y in &[self.min.y, self.max.y] {
+                for &z in &[self.min.z, self.max.z] {
+                    result.add_point(m * Tuple::point(x, y, z));
+                }
+            }
+        }
+
+        result
+    }
+
+    pub(crate) fn intersects(&self, ray: Ray) -> bool {
+        let mut t_min = f64::NEG_INFINITY;
+        let mut t_max = f64::INFINITY;
+
+        for axis in 0..3 {
+            let (axis_min, axis_max) = check_axis(
+                axis_of(self.min, axis),
+                axis_of(self.max, axis),
+                axis_of(ray.origin, axis),
+                axis_of(ray.direction, axis),
+            );
+            t_min = t_min.max(axis_min);
+            t_max = t_max.min(axis_max);
+        }
+
+        t_min <= t_max && t_max >= 0.
+    }
+}
+
+pub(crate) fn axis_of(tuple: Tuple, axis: usize) -> f64 {
+    match axis {
+        0 => tuple.x,
+        1 => tuple.y,
+        _ => tuple.z,
+    }
+}
+
+fn check_axis(min: f64, max: f64, origin: f64, direction: f64) -> (f64, f64) {
+    let t_min_numerator = min - origin;
+    let t_max_numerator = max - origin;
+
+    let (t_min, t_max) = if direction.abs() >= EPSILON {
+        (t_min_numerator / direction, t_max_numerator / direction)
+    } else {
+        (
+            t_min_numerator * f64::INFINITY,
+            t_max_numerator * f64::INFINITY,
+        )
+    };
+
+    if t_min > t_max {
+        return (t_max, t_min);
+    }
+
+    (t_min, t_max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{rotation_y, translation};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_adding_points_to_empty_box() {
+        let mut b = BoundingBox::empty();
+        b.add_point(Tuple::point(-5., 2., 0.));
+        b.add_point(Tuple::point(7., 0., -3.));
+
+        assert_eq!(Tuple::point(-5., 0., -3.), b.get_min());
+        assert_eq!(Tuple::point(7., 2., 0.), b.get_max());
+        assert_eq!(0, b.longest_axis());
+    }
+
+    #[test]
+    fn test_transforming_bounding_box() {
+        let b = BoundingBox::new(Tuple::point(-1., -1., -1.), Tuple::point(1., 1., 1.));
+
+        let t = b.transform(translation(1., 2., 3.) * rotation_y(PI / 4.));
+
+        assert_eq!(
+            Tuple::point(1. - 2f64.sqrt(), 1., 3. - 2f64.sqrt()),
+            t.get_min()
+        );
+        assert_eq!(
+            Tuple::point(1. + 2f64.sqrt(), 3., 3. + 2f64.sqrt()),
+            t.get_max()
+        );
+        assert!(!BoundingBox::infinite()
+            .transform(translation(1., 0., 0.))
+            .is_finite());
+    }
+
+    #[test]
+    fn test_intersecting_ray_with_bounding_box() {
+        let b = BoundingBox::new(Tuple::point(5., -2., 0.), Tuple::point(11., 4., 7.));
+        let cases = [
+            (
+                Tuple::point(15., 1., 2.),
+                Tuple::direction(-1., 0., 0.),
+                true,
+            ),
+            (
+                Tuple::point(8., 6., 5.),
+                Tuple::direction(0., -1., 0.),
+                true,
+            ),
+            (Tuple::point(7., 0., 3.), Tuple::direction(0., 0., 1.), true),
+            (
+                Tuple::point(9., -1., -8.),
+                Tuple::direction(2., 4., 6.),
+                false,
+            ),
+            (
+                Tuple::point(8., 2., 12.),
+                Tuple::direction(0., 0., -1.),
+                true,
+            ),
+            (
+                Tuple::point(8., 2., 12.),
+                Tuple::direction(0., 0., 1.),
+                false,
+            ),
+            (
+                Tuple::point(12., 5., 4.),
+                Tuple::direction(-1., 0., 0.),
+                false,
+            ),
+        ];
+
+        for (origin, direction, expected) in cases.iter() {
+            let r = Ray::new(*origin, direction.normalize());
+
+            assert_eq!(*expected, b.intersects(r));
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use crate::math::tuple::Tuple;
+use crate::primitives::bounding_box::BoundingBox;
 use crate::primitives::shape::{Shape, ShapeProps};
 use crate::scene::tracing::intersection::{Intersections, Intersection};
 use crate::scene::tracing::ray::Ray;
 use crate::math::f64_eq;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Cone {
     props: ShapeProps,
     min: f64,
@@ -28,6 +29,10 @@ impl Cone {
 }
 
 impl Shape for Cone {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
     fn get_props(&self) -> &ShapeProps {
         &self.props
     }
@@ -40,7 +45,7 @@ impl Shape for Cone {
         todo!()
     }
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections> {
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
         let a = ray.direction.x.powi(2) - ray.direction.y.powi(2) + ray.direction.z.powi(2);
         let b = 2. * ray.origin.x * ray.direction.x - 2. * ray.origin.y * ray.direction.y + 2. * ray.origin.z * ray.direction.z;
         let c = ray.origin.x.powi(2) - ray.origin.y.powi(2) + ray.origin.z.powi(2);
@@ -80,6 +85,15 @@ impl Shape for Cone {
 
         Some(xs)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        let radius = self.min.abs().max(self.max.abs());
+
+        BoundingBox::new(
+            Tuple::point(-radius, self.min, -radius),
+            Tuple::point(radius, self.max, radius),
+        )
+    }
 }
 
 fn min_max(a: f64, b: f64) -> (f64, f64) {
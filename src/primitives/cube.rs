@@ -1,24 +1,34 @@
 use crate::math::tuple::Tuple;
 use crate::math::{f64_eq, EPSILON};
-use crate::primitives::shape::{Shape, ShapeProps};
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
 use crate::scene::tracing::intersection::{Intersection, Intersections};
 use crate::scene::tracing::ray::Ray;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Cube {
     props: ShapeProps,
+    min: Tuple,
+    max: Tuple,
 }
 
 impl Cube {
     pub(crate) fn new() -> Cube {
+        Cube::with_bounds(Tuple::point(-1., -1., -1.), Tuple::point(1., 1., 1.))
+    }
+
+    // axis aligned box between the two corners, e.g. a room without a scaling transformation
+    pub(crate) fn with_bounds(min: Tuple, max: Tuple) -> Cube {
         Cube {
             props: ShapeProps::default(),
+            min,
+            max,
         }
     }
 
-    fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
-        let t_min_numerator = -1. - origin;
-        let t_max_numerator = 1. - origin;
+    fn check_axis(min: f64, max: f64, origin: f64, direction: f64) -> (f64, f64) {
+        let t_min_numerator = min - origin;
+        let t_max_numerator = max - origin;
 
         let (t_min, t_max) = if direction.abs() >= EPSILON {
             (t_min_numerator / direction, t_max_numerator / direction)
@@ -38,6 +48,17 @@ impl Cube {
 }
 
 impl Shape for Cube {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Cube {
+            min: self.min,
+            max: self.max,
+        })
+    }
+
     fn get_props(&self) -> &ShapeProps {
         &self.props
     }
@@ -46,22 +67,29 @@ impl Shape for Cube {
         &mut self.props
     }
 
+    // normal of the face the point is closest to, on edges x wins over y over z
     fn local_normal_at(&self, point: Tuple) -> Tuple {
-        let max_c = point.x.abs().max(point.y.abs().max(point.z.abs()));
+        let (x_distance, x_sign) = face_distance(point.x, self.min.x, self.max.x);
+        let (y_distance, y_sign) = face_distance(point.y, self.min.y, self.max.y);
+        let (z_distance, z_sign) = face_distance(point.z, self.min.z, self.max.z);
+        let min_distance = x_distance.min(y_distance.min(z_distance));
 
-        if f64_eq(max_c, point.x.abs()) {
-            return Tuple::direction(point.x, 0., 0.);
-        } else if f64_eq(max_c, point.y.abs()) {
-            return Tuple::direction(0., point.y, 0.);
+        if f64_eq(min_distance, x_distance) {
+            return Tuple::direction(x_sign, 0., 0.);
+        } else if f64_eq(min_distance, y_distance) {
+            return Tuple::direction(0., y_sign, 0.);
         }
 
-        Tuple::direction(0., 0., point.z)
+        Tuple::direction(0., 0., z_sign)
     }
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections> {
-        let (x_min, x_max) = Cube::check_axis(ray.origin.x, ray.direction.x);
-        let (y_min, y_max) = Cube::check_axis(ray.origin.y, ray.direction.y);
-        let (z_min, z_max) = Cube::check_axis(ray.origin.z, ray.direction.z);
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let (x_min, x_max) =
+            Cube::check_axis(self.min.x, self.max.x, ray.origin.x, ray.direction.x);
+        let (y_min, y_max) =
+            Cube::check_axis(self.min.y, self.max.y, ray.origin.y, ray.direction.y);
+        let (z_min, z_max) =
+            Cube::check_axis(self.min.z, self.max.z, ray.origin.z, ray.direction.z);
 
         let t_min = x_min.max(y_min.max(z_min));
         let t_max = x_max.min(y_max.min(z_max));
@@ -76,6 +104,22 @@ impl Shape for Cube {
 
         Some(xs)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(self.min, self.max)
+    }
+}
+
+// distance of the coordinate to the nearer of both faces and the direction that face points to
+fn face_distance(value: f64, min: f64, max: f64) -> (f64, f64) {
+    let to_min = (value - min).abs();
+    let to_max = (value - max).abs();
+
+    if to_max <= to_min {
+        (to_max, 1.)
+    } else {
+        (to_min, -1.)
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +252,81 @@ mod tests {
             test_ray_normal(*point, *normal);
         }
     }
+
+    #[test]
+    fn test_ray_intersects_cube_with_bounds() {
+        let c = Cube::with_bounds(Tuple::point(-2., -2., -2.), Tuple::point(2., 2., 2.));
+        let cases = [
+            (
+                Tuple::point(5., 0.5, 0.),
+                Tuple::direction(-1., 0., 0.),
+                3.,
+                7.,
+            ),
+            (
+                Tuple::point(0.5, -5., 0.),
+                Tuple::direction(0., 1., 0.),
+                3.,
+                7.,
+            ),
+            (
+                Tuple::point(0.5, 0., 5.),
+                Tuple::direction(0., 0., -1.),
+                3.,
+                7.,
+            ),
+            (
+                Tuple::point(0., 0.5, 0.),
+                Tuple::direction(0., 0., 1.),
+                -2.,
+                2.,
+            ),
+        ];
+
+        for (origin, direction, t1, t2) in cases.iter() {
+            let xs = c.local_intersect(Ray::new(*origin, *direction)).unwrap();
+
+            assert_eq!(*t1, xs.first().unwrap().t);
+            assert_eq!(*t2, xs.last().unwrap().t);
+        }
+
+        let r = Ray::new(Tuple::point(3., 0., 3.), Tuple::direction(0., 0., -1.));
+        assert!(c.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn test_normal_on_surface_of_cube_with_bounds() {
+        let c = Cube::with_bounds(Tuple::point(-2., -2., -2.), Tuple::point(2., 2., 2.));
+        let cases = [
+            (Tuple::point(2., 0.5, -0.8), Tuple::direction(1., 0., 0.)),
+            (Tuple::point(-2., -1.2, 1.9), Tuple::direction(-1., 0., 0.)),
+            (Tuple::point(-0.4, 2., -0.1), Tuple::direction(0., 1., 0.)),
+            (Tuple::point(0.3, -2., -1.7), Tuple::direction(0., -1., 0.)),
+            (Tuple::point(-0.6, 0.3, 2.), Tuple::direction(0., 0., 1.)),
+            (Tuple::point(1.4, 0.4, -2.), Tuple::direction(0., 0., -1.)),
+            (Tuple::point(2., 2., 2.), Tuple::direction(1., 0., 0.)),
+        ];
+
+        for (point, normal) in cases.iter() {
+            assert_eq!(*normal, c.local_normal_at(*point));
+        }
+    }
+
+    #[test]
+    fn test_normal_on_surface_of_off_center_cube() {
+        let c = Cube::with_bounds(Tuple::point(0., 0., 0.), Tuple::point(4., 1., 2.));
+
+        assert_eq!(
+            Tuple::direction(-1., 0., 0.),
+            c.local_normal_at(Tuple::point(0., 0.5, 1.))
+        );
+        assert_eq!(
+            Tuple::direction(0., 1., 0.),
+            c.local_normal_at(Tuple::point(3., 1., 1.))
+        );
+        assert_eq!(
+            Tuple::direction(0., 0., 1.),
+            c.local_normal_at(Tuple::point(3.5, 0.5, 2.))
+        );
+    }
 }
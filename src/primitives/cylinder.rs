@@ -1,10 +1,11 @@
 use crate::math::tuple::Tuple;
 use crate::math::{f64_eq, EPSILON};
-use crate::primitives::shape::{Shape, ShapeProps};
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
 use crate::scene::tracing::intersection::{Intersection, Intersections};
 use crate::scene::tracing::ray::Ray;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Cylinder {
     props: ShapeProps,
     min: f64,
@@ -35,7 +36,7 @@ impl Cylinder {
         self.closed = true;
     }
 
-    fn intersect_caps(&self, ray: Ray) -> Intersections {
+    fn intersect_caps(&self, ray: Ray) -> Intersections<'_> {
         let mut xs = Intersections::new();
 
         // caps only matter if the cylinder is closed, and might possibly be
@@ -58,11 +59,26 @@ impl Cylinder {
             xs.push(Intersection::new(t, self));
         }
 
+        // a ray pointing downwards hits the upper cap first
+        xs.sort();
+
         xs
     }
 }
 
 impl Shape for Cylinder {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Cylinder {
+            min: Some(self.min).filter(|min| min.is_finite()),
+            max: Some(self.max).filter(|max| max.is_finite()),
+            closed: self.closed,
+        })
+    }
+
     fn get_props(&self) -> &ShapeProps {
         &self.props
     }
@@ -75,18 +91,19 @@ impl Shape for Cylinder {
         // compute the square of the distance from the y axis
         let dist = point.x.powi(2) + point.z.powi(2);
 
-        if dist < 1. && point.y >= self.max - EPSILON {
+        // an open cylinder has no caps, rounding may still put a point on its rim inside
+        if self.closed && dist < 1. && point.y >= self.max - EPSILON {
             return Tuple::direction(0., 1., 0.);
         }
 
-        if dist < 1. && point.y <= self.min + EPSILON {
+        if self.closed && dist < 1. && point.y <= self.min + EPSILON {
             return Tuple::direction(0., -1., 0.);
         }
 
         Tuple::direction(point.x, 0., point.z)
     }
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections> {
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
         let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
 
         // ray is parallel to the y axis
@@ -115,10 +132,19 @@ impl Shape for Cylinder {
             xs.push(Intersection::new(t1, self));
         }
 
+        // caps may be hit before or between the walls
         xs.merge(self.intersect_caps(ray));
+        xs.sort();
 
         Some(xs)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Tuple::point(-1., self.min, -1.),
+            Tuple::point(1., self.max, 1.),
+        )
+    }
 }
 
 fn min_max(a: f64, b: f64) -> (f64, f64) {
@@ -146,10 +172,7 @@ mod tests {
 
         let xs = cyl.local_intersect(r);
 
-        match xs {
-            Some(intersections) => assert_eq!(0, intersections.len()),
-            None => (),
-        }
+        assert!(xs.is_none_or(|xs| xs.is_empty()));
     }
 
     #[test]
@@ -271,6 +294,7 @@ mod tests {
         let xs = cyl.local_intersect(r).unwrap();
 
         assert_eq!(2, xs.len());
+        assert!(xs.is_sorted());
     }
 
     #[test]
@@ -288,6 +312,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_intersections_of_closed_cylinder_are_sorted() {
+        let mut cyl = Cylinder::with_min_max(1., 2.);
+        cyl.close();
+        // enters through the upper cap and leaves through the wall
+        let r = Ray::new(
+            Tuple::point(0., 3., 0.5),
+            Tuple::direction(0., -1., -1.).normalize(),
+        );
+
+        let xs = cyl.intersect(r).unwrap();
+
+        assert_eq!(2, xs.len());
+        assert!(xs.get(0).unwrap().t < xs.get(1).unwrap().t);
+    }
+
     fn assert_normal_with_caps(point: Tuple, normal: Tuple) {
         let mut cyl = Cylinder::with_min_max(1., 2.);
         cyl.close();
@@ -311,4 +351,18 @@ mod tests {
             assert_normal_with_caps(*point, *normal);
         }
     }
+
+    #[test]
+    fn test_normal_of_open_cylinder_at_its_rim_is_side_normal() {
+        let cyl = Cylinder::with_min_max(1., 2.);
+
+        assert_eq!(
+            Tuple::direction(0.99999, 0., 0.),
+            cyl.local_normal_at(Tuple::point(0.99999, 2., 0.))
+        );
+        assert_eq!(
+            Tuple::direction(0., 0., -0.99999),
+            cyl.local_normal_at(Tuple::point(0., 1., -0.99999))
+        );
+    }
 }
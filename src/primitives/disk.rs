@@ -0,0 +1,140 @@
+use crate::math::tuple::Tuple;
+use crate::math::EPSILON;
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
+use crate::scene::tracing::intersection::{Intersection, Intersections};
+use crate::scene::tracing::ray::Ray;
+
+// finite part of the xz plane around the origin, a ring if the inner radius is above 0
+#[derive(Clone, Debug)]
+pub(crate) struct Disk {
+    props: ShapeProps,
+    inner: f64,
+    outer: f64,
+}
+
+impl Disk {
+    pub(crate) fn new() -> Disk {
+        Disk::with_radii(0., 1.)
+    }
+
+    pub(crate) fn with_radii(inner: f64, outer: f64) -> Disk {
+        Disk {
+            props: ShapeProps::default(),
+            inner,
+            outer,
+        }
+    }
+}
+
+impl Shape for Disk {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Disk {
+            inner: self.inner,
+            outer: self.outer,
+        })
+    }
+
+    fn get_props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_normal_at(&self, _: Tuple) -> Tuple {
+        Tuple::direction(0., 1., 0.)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        if ray.direction.y.abs() < EPSILON {
+            return None;
+        }
+        let t = -ray.origin.y / ray.direction.y;
+
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+        let distance = (x.powi(2) + z.powi(2)).sqrt();
+        if distance < self.inner || distance > self.outer {
+            return None;
+        }
+
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(t, self));
+
+        Some(xs)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Tuple::point(-self.outer, 0., -self.outer),
+            Tuple::point(self.outer, 0., self.outer),
+        )
+    }
+}
+
+impl PartialEq for Disk {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_of_disk_is_constant_everywhere() {
+        let d = Disk::with_radii(0.5, 2.);
+
+        assert_eq!(
+            Tuple::direction(0., 1., 0.),
+            d.local_normal_at(Tuple::point(1., 0., 0.))
+        );
+        assert_eq!(
+            Tuple::direction(0., 1., 0.),
+            d.local_normal_at(Tuple::point(0., 0., -1.5))
+        );
+    }
+
+    #[test]
+    fn test_ray_intersects_disk_within_ring() {
+        let d = Disk::with_radii(0.5, 2.);
+        let r = Ray::new(Tuple::point(1., 1., 1.), Tuple::direction(0., -1., 0.));
+
+        let xs = d.local_intersect(r).unwrap();
+
+        assert_eq!(1, xs.len());
+        assert_eq!(1., xs.get(0).unwrap().t);
+    }
+
+    #[test]
+    fn test_ray_misses_disk_in_inner_hole() {
+        let d = Disk::with_radii(0.5, 2.);
+        let r = Ray::new(Tuple::point(0.2, 1., -0.2), Tuple::direction(0., -1., 0.));
+
+        assert!(d.local_intersect(r).is_none());
+    }
+
+    #[test]
+    fn test_ray_misses_disk_beyond_outer_radius() {
+        let d = Disk::with_radii(0.5, 2.);
+        let r = Ray::new(
+            Tuple::point(0., 1., -5.),
+            Tuple::direction(0., -1., 1.).normalize(),
+        );
+
+        assert!(d.local_intersect(r).is_none());
+        assert!(Disk::new()
+            .local_intersect(Ray::new(
+                Tuple::point(1.5, 1., 0.),
+                Tuple::direction(0., -1., 0.)
+            ))
+            .is_none());
+    }
+}
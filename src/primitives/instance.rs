@@ -0,0 +1,103 @@
+use crate::math::tuple::Tuple;
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeProps};
+use crate::scene::tracing::intersection::{Intersection, Intersections};
+use crate::scene::tracing::ray::Ray;
+use std::sync::Arc;
+
+// places shared geometry with its own transformation and material, so many identical objects
+// don't each need a copy. The geometry's own transformation is applied inside the instance.
+#[derive(Clone, Debug)]
+pub(crate) struct Instance {
+    props: ShapeProps,
+    geometry: Arc<dyn Shape>,
+}
+
+impl Instance {
+    pub(crate) fn new(geometry: Arc<dyn Shape>) -> Instance {
+        Instance {
+            props: ShapeProps::default(),
+            geometry,
+        }
+    }
+}
+
+impl Shape for Instance {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn get_props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        self.geometry.normal_at(point)
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        let geometry_xs = self.geometry.intersect(ray)?;
+
+        // the hits have to point to the instance, it has the material and transformation
+        let mut xs = Intersections::new();
+        for intersection in &geometry_xs {
+            xs.push(Intersection::new(intersection.t, self));
+        }
+
+        Some(xs)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.geometry.world_bounds()
+    }
+
+    fn thickness(&self) -> f64 {
+        self.geometry.thickness()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::{scaling, translation};
+    use crate::primitives::sphere::Sphere;
+
+    #[test]
+    fn test_instances_share_geometry_but_intersect_independently() {
+        let sphere: Arc<dyn Shape> = Arc::new(Sphere::new());
+        let left = Instance::new(sphere.clone()).transformed(translation(-3., 0., 0.));
+        let right = Instance::new(sphere).transformed(translation(3., 0., 0.));
+
+        let r = Ray::new(Tuple::point(-3., 0., -5.), Tuple::direction(0., 0., 1.));
+        let xs = left.intersect(r).unwrap();
+        assert_eq!(2, xs.len());
+        assert_eq!(4., xs[0].t);
+        assert_eq!(6., xs[1].t);
+        assert_eq!(left.get_id(), xs[0].object.get_id());
+        assert!(right.intersect(r).is_none());
+
+        let r = Ray::new(Tuple::point(3., 0., -5.), Tuple::direction(0., 0., 1.));
+        assert!(left.intersect(r).is_none());
+        assert_eq!(2, right.intersect(r).unwrap().len());
+        assert_eq!(
+            Tuple::direction(0., 0., -1.),
+            right.normal_at(Tuple::point(3., 0., -1.))
+        );
+    }
+
+    #[test]
+    fn test_instance_applies_the_geometry_transform() {
+        let sphere: Arc<dyn Shape> = Arc::new(Sphere::new().transformed(scaling(2., 2., 2.)));
+        let instance = Instance::new(sphere).transformed(translation(0., 0., 10.));
+
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let xs = instance.intersect(r).unwrap();
+
+        assert_eq!(13., xs[0].t);
+        assert_eq!(17., xs[1].t);
+    }
+}
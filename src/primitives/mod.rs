@@ -1,6 +1,10 @@
+pub(crate) mod bounding_box;
 pub(crate) mod cone;
 pub(crate) mod cube;
 pub(crate) mod cylinder;
+pub(crate) mod disk;
+pub(crate) mod instance;
 pub(crate) mod plane;
 pub(crate) mod shape;
 pub(crate) mod sphere;
+pub(crate) mod torus;
@@ -1,23 +1,40 @@
 use crate::math::tuple::Tuple;
 use crate::math::EPSILON;
-use crate::primitives::shape::{Shape, ShapeProps};
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
 use crate::scene::tracing::intersection::{Intersection, Intersections};
 use crate::scene::tracing::ray::Ray;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Plane {
     props: ShapeProps,
+    thickness: f64,
 }
 
 impl Plane {
     pub(crate) fn new() -> Plane {
         Plane {
             props: ShapeProps::default(),
+            thickness: 0.,
         }
     }
+
+    // refracts like a glass sheet of this thickness in world units
+    pub(crate) fn set_thickness(&mut self, thickness: f64) {
+        self.thickness = thickness
+    }
 }
 
 impl Shape for Plane {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Plane {
+            thickness: self.thickness,
+        })
+    }
+
     fn get_props(&self) -> &ShapeProps {
         &self.props
     }
@@ -30,7 +47,7 @@ impl Shape for Plane {
         Tuple::direction(0., 1., 0.)
     }
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections> {
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
         if ray.direction.y.abs() < EPSILON {
             return None;
         }
@@ -41,6 +58,10 @@ impl Shape for Plane {
 
         Some(xs)
     }
+
+    fn thickness(&self) -> f64 {
+        self.thickness
+    }
 }
 
 impl PartialEq for Plane {
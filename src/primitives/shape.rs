@@ -1,10 +1,19 @@
 use crate::math::matrix::M4;
+use crate::math::transformation::{rotation_x, rotation_y, rotation_z, scaling, translation};
 use crate::math::tuple::Tuple;
 use crate::pattern::Pattern;
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::cube::Cube;
+use crate::primitives::cylinder::Cylinder;
+use crate::primitives::disk::Disk;
+use crate::primitives::plane::Plane;
+use crate::primitives::sphere::Sphere;
+use crate::primitives::torus::Torus;
 use crate::scene::shading::color::Color;
-use crate::scene::shading::material::Material;
+use crate::scene::shading::material::{Material, RefractiveIndex};
 use crate::scene::tracing::intersection::Intersections;
 use crate::scene::tracing::ray::Ray;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use uuid::Uuid;
 
@@ -15,35 +24,296 @@ pub(crate) trait Shape: Debug + Sync + Send {
 
     fn local_normal_at(&self, point: Tuple) -> Tuple;
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections>;
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>>;
+
+    // boxed copy with its own id, e.g. to instance the same geometry at several places
+    fn clone_box(&self) -> Box<dyn Shape>;
+
+    // bounds in object space, shapes without finite bounds keep the infinite default
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::infinite()
+    }
+
+    // thin surfaces without a volume (planes) can act like a sheet of glass of this thickness
+    fn thickness(&self) -> f64 {
+        0.
+    }
+
+    fn world_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.get_props().get_transform())
+    }
 
     fn get_id(&self) -> Uuid {
         self.get_props().id
     }
 
+    // boxed copy that keeps the id, for copies of a whole world that refer to the same objects
+    fn duplicate(&self) -> Box<dyn Shape> {
+        let mut copy = self.clone_box();
+        copy.mut_props().id = self.get_id();
+        copy
+    }
+
+    fn set_transform(&mut self, transform: M4) {
+        self.mut_props().set_transform(transform)
+    }
+
+    fn material(&self) -> &Material {
+        self.get_props().get_material()
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.mut_props().set_material(material)
+    }
+
+    // transparent material with the refractive index of glass, everything else untouched
+    fn make_glass(&mut self) {
+        self.mut_props().set_material_transparency(1.0);
+        self.mut_props()
+            .set_material_refractive_index(RefractiveIndex::Glass.value());
+    }
+
+    // geometry parameters needed to save and rebuild the shape, None if it can't be saved
+    fn kind(&self) -> Option<ShapeKind> {
+        None
+    }
+
+    // chainable variant of set_transform, e.g. Sphere::new().transformed(translation(..))
+    fn transformed(mut self, transform: M4) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_transform(transform);
+        self
+    }
+
     fn normal_at(&self, point: Tuple) -> Tuple {
-        let inverse_transform = self.get_props().get_transform().inverse().unwrap();
-        let local_point = inverse_transform * point;
+        // degenerate objects are never hit, but keep callers free of NaN anyway
+        if !self.get_props().is_invertible() {
+            return Tuple::direction(0., 1., 0.);
+        }
+
+        let local_point = self.get_props().get_inverse_transform() * point;
         let local_normal = self.local_normal_at(local_point);
 
-        let mut world_normal = inverse_transform.transpose() * local_normal;
+        let mut world_normal = self.get_props().get_inverse_transpose() * local_normal;
         world_normal.w = 0.;
 
         world_normal.normalize()
     }
 
-    fn intersect(&self, ray: Ray) -> Option<Intersections> {
-        let local_ray = ray.transform(self.get_props().get_transform().inverse().unwrap());
+    fn intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        // a flattened object has no volume or surface a ray could hit
+        if !self.get_props().is_invertible() {
+            return None;
+        }
+
+        let local_ray = ray.transform_by(self.get_props().get_inverse_transform());
+        let mut xs = self.local_intersect(local_ray);
+
+        let clip_planes = &self.get_props().clip_planes;
+        if let Some(intersections) = xs.as_mut() {
+            if !clip_planes.is_empty() {
+                intersections.retain(|i| {
+                    let point = local_ray.position(i.t);
+                    clip_planes.iter().all(|plane| plane.keeps(point))
+                });
+            }
+        }
+
+        // hit detection and refraction rely on every shape returning ascending t
+        debug_assert!(xs.as_ref().is_none_or(|xs| xs.is_sorted()));
 
-        self.local_intersect(local_ray)
+        // shapes may report a miss as an empty set, callers only have to check for None
+        xs.filter(|xs| !xs.is_empty())
     }
 }
 
-#[derive(Debug)]
+impl Clone for Box<dyn Shape> {
+    fn clone(&self) -> Box<dyn Shape> {
+        self.clone_box()
+    }
+}
+
+// cuts a shape open, only the side the normal points to stays visible. Point and normal are
+// in object space, so the cut moves along with the shape.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ClipPlane {
+    point: Tuple,
+    normal: Tuple,
+}
+
+impl ClipPlane {
+    pub(crate) fn new(point: Tuple, normal: Tuple) -> ClipPlane {
+        ClipPlane { point, normal }
+    }
+
+    fn keeps(&self, point: Tuple) -> bool {
+        (point - self.point).dot(self.normal) >= 0.
+    }
+}
+
+// saved shapes are tagged with their type, unbounded extents are saved as None
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ShapeKind {
+    Sphere,
+    Plane {
+        thickness: f64,
+    },
+    Cube {
+        min: Tuple,
+        max: Tuple,
+    },
+    Cylinder {
+        min: Option<f64>,
+        max: Option<f64>,
+        closed: bool,
+    },
+    Disk {
+        inner: f64,
+        outer: f64,
+    },
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+}
+
+impl ShapeKind {
+    pub(crate) fn build(&self, props: ShapeProps) -> Box<dyn Shape> {
+        let mut shape: Box<dyn Shape> = match *self {
+            ShapeKind::Sphere => Box::new(Sphere::new()),
+            ShapeKind::Plane { thickness } => {
+                let mut plane = Plane::new();
+                plane.set_thickness(thickness);
+                Box::new(plane)
+            }
+            ShapeKind::Cube { min, max } => Box::new(Cube::with_bounds(min, max)),
+            ShapeKind::Cylinder { min, max, closed } => {
+                let mut cylinder = Cylinder::with_min_max(
+                    min.unwrap_or(f64::NEG_INFINITY),
+                    max.unwrap_or(f64::INFINITY),
+                );
+                if closed {
+                    cylinder.close();
+                }
+                Box::new(cylinder)
+            }
+            ShapeKind::Disk { inner, outer } => Box::new(Disk::with_radii(inner, outer)),
+            ShapeKind::Torus {
+                major_radius,
+                minor_radius,
+            } => Box::new(Torus::new(major_radius, minor_radius)),
+        };
+        *shape.mut_props() = props;
+
+        shape
+    }
+}
+
+// (de)serializes the objects of a world as a list of tagged shapes
+pub(crate) mod saved_shapes {
+    use super::{Shape, ShapeKind, ShapeProps};
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct SavedShape<P> {
+        #[serde(flatten)]
+        kind: ShapeKind,
+        props: P,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        shapes: &[Box<dyn Shape>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut saved = Vec::with_capacity(shapes.len());
+        for shape in shapes {
+            let kind = shape
+                .kind()
+                .ok_or_else(|| S::Error::custom(format!("can't save {:?}", shape)))?;
+            saved.push(SavedShape {
+                kind,
+                props: shape.get_props(),
+            });
+        }
+
+        saved.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Box<dyn Shape>>, D::Error> {
+        let saved: Vec<SavedShape<ShapeProps>> = Vec::deserialize(deserializer)?;
+
+        Ok(saved
+            .into_iter()
+            .map(|shape| shape.kind.build(shape.props))
+            .collect())
+    }
+}
+
+// loaded props get a new id, the cached inverses are computed from the saved transform
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "SavedShapeProps")]
 pub(crate) struct ShapeProps {
+    #[serde(skip_serializing)]
     id: Uuid,
     transform: M4,
+    // cached, because they are needed for every ray and normal
+    #[serde(skip_serializing)]
+    inverse_transform: M4,
+    #[serde(skip_serializing)]
+    inverse_transpose: M4,
+    #[serde(skip_serializing)]
+    invertible: bool,
     material: Material,
+    clip_planes: Vec<ClipPlane>,
+    // shadow rays pass through objects that don't cast shadows, e.g. lamp covers
+    casts_shadow: bool,
+    // surfaces that don't receive shadows are always lit, e.g. a floor that should stay bright
+    receives_shadow: bool,
+}
+
+#[derive(Deserialize)]
+struct SavedShapeProps {
+    transform: M4,
+    material: Material,
+    clip_planes: Vec<ClipPlane>,
+    casts_shadow: bool,
+    #[serde(default = "receives_shadow_default")]
+    receives_shadow: bool,
+}
+
+fn receives_shadow_default() -> bool {
+    true
+}
+
+impl From<SavedShapeProps> for ShapeProps {
+    fn from(saved: SavedShapeProps) -> ShapeProps {
+        let mut props = ShapeProps::default();
+        props.set_transform(saved.transform);
+        props.material = saved.material;
+        props.clip_planes = saved.clip_planes;
+        props.casts_shadow = saved.casts_shadow;
+        props.receives_shadow = saved.receives_shadow;
+
+        props
+    }
+}
+
+// a clone is another object in the scene, so it gets its own id
+impl Clone for ShapeProps {
+    fn clone(&self) -> ShapeProps {
+        ShapeProps {
+            id: Uuid::new_v4(),
+            material: self.material.clone(),
+            clip_planes: self.clip_planes.clone(),
+            ..*self
+        }
+    }
 }
 
 impl ShapeProps {
@@ -51,7 +321,13 @@ impl ShapeProps {
         ShapeProps {
             id: Uuid::new_v4(),
             transform: M4::identity(),
+            inverse_transform: M4::identity(),
+            inverse_transpose: M4::identity(),
+            invertible: true,
             material: Material::new(),
+            clip_planes: vec![],
+            casts_shadow: true,
+            receives_shadow: true,
         }
     }
 
@@ -60,7 +336,54 @@ impl ShapeProps {
     }
 
     pub(crate) fn set_transform(&mut self, new: M4) {
-        self.transform = new
+        // a degenerate transformation is reported by World::validate, rendering just skips
+        // the object instead of panicking mid render
+        let inverse = new.inverse();
+        self.invertible = inverse.is_some();
+        let inverse = inverse.unwrap_or_else(|| M4::from([f64::NAN; 16]));
+
+        self.transform = new;
+        self.inverse_transform = inverse;
+        self.inverse_transpose = inverse.transpose();
+    }
+
+    // fluent helpers, each one is multiplied onto the right of the current transformation, so
+    // props.translate(..).scale(..) equals translation(..) * scaling(..)
+    pub(crate) fn translate(&mut self, x: f64, y: f64, z: f64) -> &mut ShapeProps {
+        self.set_transform(self.transform * translation(x, y, z));
+        self
+    }
+
+    pub(crate) fn scale(&mut self, x: f64, y: f64, z: f64) -> &mut ShapeProps {
+        self.set_transform(self.transform * scaling(x, y, z));
+        self
+    }
+
+    pub(crate) fn rotate_x(&mut self, radians: f64) -> &mut ShapeProps {
+        self.set_transform(self.transform * rotation_x(radians));
+        self
+    }
+
+    pub(crate) fn rotate_y(&mut self, radians: f64) -> &mut ShapeProps {
+        self.set_transform(self.transform * rotation_y(radians));
+        self
+    }
+
+    pub(crate) fn rotate_z(&mut self, radians: f64) -> &mut ShapeProps {
+        self.set_transform(self.transform * rotation_z(radians));
+        self
+    }
+
+    pub(crate) fn is_invertible(&self) -> bool {
+        self.invertible
+    }
+
+    pub(crate) fn get_inverse_transform(&self) -> &M4 {
+        &self.inverse_transform
+    }
+
+    pub(crate) fn get_inverse_transpose(&self) -> &M4 {
+        &self.inverse_transpose
     }
 
     pub(crate) fn get_material(&self) -> &Material {
@@ -102,9 +425,45 @@ impl ShapeProps {
         self.material.set_refractive_index(new);
     }
 
+    pub(crate) fn set_material_fresnel(&mut self, new: bool) {
+        self.material.set_fresnel(new);
+    }
+
+    pub(crate) fn set_material_emission(&mut self, new: Color) {
+        self.material.set_emission(new);
+    }
+
     pub(crate) fn set_pattern(&mut self, new: Box<dyn Pattern>) {
         self.material.set_pattern(new)
     }
+
+    pub(crate) fn set_normal_map(&mut self, new: Box<dyn Pattern>) {
+        self.material.set_normal_map(new)
+    }
+
+    pub(crate) fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    pub(crate) fn set_casts_shadow(&mut self, new: bool) {
+        self.casts_shadow = new
+    }
+
+    pub(crate) fn receives_shadow(&self) -> bool {
+        self.receives_shadow
+    }
+
+    pub(crate) fn set_receives_shadow(&mut self, new: bool) {
+        self.receives_shadow = new
+    }
+
+    pub(crate) fn is_reflective_surface(&self) -> bool {
+        self.material.get_reflective() > 0.
+    }
+
+    pub(crate) fn add_clip_plane(&mut self, plane: ClipPlane) {
+        self.clip_planes.push(plane);
+    }
 }
 
 #[cfg(test)]
@@ -113,7 +472,7 @@ mod tests {
     use crate::math::transformation::{rotation_z, scaling, translation};
     use std::f64::consts::{FRAC_1_SQRT_2, PI};
 
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     struct TestShape {
         props: ShapeProps,
     }
@@ -126,7 +485,7 @@ mod tests {
         }
 
         fn to_local_ray(&self, ray: Ray) -> Ray {
-            ray.transform(self.props.transform.inverse().unwrap())
+            ray.transform_by(self.props.get_inverse_transform())
         }
     }
 
@@ -137,6 +496,10 @@ mod tests {
     }
 
     impl Shape for TestShape {
+        fn clone_box(&self) -> Box<dyn Shape> {
+            Box::new(self.clone())
+        }
+
         fn get_props(&self) -> &ShapeProps {
             &self.props
         }
@@ -149,7 +512,7 @@ mod tests {
             Tuple::direction(point.x, point.y, point.z)
         }
 
-        fn local_intersect(&self, _ray: Ray) -> Option<Intersections> {
+        fn local_intersect(&self, _ray: Ray) -> Option<Intersections<'_>> {
             None
         }
     }
@@ -169,6 +532,55 @@ mod tests {
         assert_eq!(s.props.transform, translation(2., 3., 4.));
     }
 
+    #[test]
+    fn test_transformed_applies_matrix_and_returns_shape() {
+        let s = TestShape::new()
+            .transformed(translation(2., 3., 4.))
+            .transformed(scaling(2., 2., 2.));
+
+        assert_eq!(s.props.transform, scaling(2., 2., 2.));
+
+        let mut s = TestShape::new();
+        s.set_transform(translation(2., 3., 4.));
+
+        assert_eq!(s.props.transform, translation(2., 3., 4.));
+    }
+
+    #[test]
+    fn test_set_transform_caches_inverse() {
+        let mut s = TestShape::new();
+        let m = translation(2., 3., 4.) * scaling(1., 0.5, 2.) * rotation_z(PI / 5.);
+        s.props.set_transform(m);
+
+        assert_eq!(m.inverse().unwrap(), *s.props.get_inverse_transform());
+        assert_eq!(
+            m.inverse().unwrap().transpose(),
+            *s.props.get_inverse_transpose()
+        );
+    }
+
+    #[test]
+    fn test_fluent_transform_helpers_post_multiply() {
+        let mut s = TestShape::new();
+        s.props.translate(1., 0., 0.).scale(2., 2., 2.);
+
+        assert_eq!(
+            translation(1., 0., 0.) * scaling(2., 2., 2.),
+            s.props.transform
+        );
+
+        let mut s = TestShape::new();
+        s.props
+            .rotate_x(PI / 2.)
+            .rotate_y(PI / 4.)
+            .rotate_z(PI / 5.);
+
+        assert_eq!(
+            rotation_x(PI / 2.) * rotation_y(PI / 4.) * rotation_z(PI / 5.),
+            s.props.get_transform()
+        );
+    }
+
     #[test]
     fn test_shape_default_material() {
         let s = TestShape::new();
@@ -176,6 +588,48 @@ mod tests {
         assert_eq!(s.props.material, Material::new());
     }
 
+    #[test]
+    fn test_shadow_and_reflection_queries() {
+        let mut s = TestShape::new();
+
+        assert!(s.props.casts_shadow());
+        assert!(s.props.receives_shadow());
+        assert!(!s.props.is_reflective_surface());
+
+        s.props.set_receives_shadow(false);
+        s.props.set_material_reflective(0.3);
+
+        assert!(!s.props.receives_shadow());
+        assert!(s.props.is_reflective_surface());
+    }
+
+    #[test]
+    fn test_material_pass_through_matches_props() {
+        let mut s = TestShape::new();
+        let mut m = Material::new();
+        m.set_ambient(1.);
+        m.set_color(Color::new(0.2, 0.3, 0.4));
+
+        s.set_material(m);
+
+        assert_eq!(s.get_props().get_material(), s.material());
+        assert_eq!(Color::new(0.2, 0.3, 0.4), s.material().get_color());
+    }
+
+    #[test]
+    fn test_make_glass_only_changes_transparency_and_refractive_index() {
+        let mut s = TestShape::new();
+        s.props.set_material_color(Color::new(1., 0., 0.));
+        s.make_glass();
+
+        let mut e = Material::new();
+        e.set_color(Color::new(1., 0., 0.));
+        e.set_transparency(1.0);
+        e.set_refractive_index(1.5);
+
+        assert_eq!(s.props.material, e);
+    }
+
     #[test]
     fn test_shape_assigning_material() {
         let mut s = TestShape::new();
@@ -1,14 +1,23 @@
 use crate::math::tuple::Tuple;
-use crate::primitives::shape::{Shape, ShapeProps};
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
 use crate::scene::tracing::intersection::{Intersection, Intersections};
 use crate::scene::tracing::ray::Ray;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Sphere {
     props: ShapeProps,
 }
 
 impl Shape for Sphere {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Sphere)
+    }
+
     fn get_props(&self) -> &ShapeProps {
         &self.props
     }
@@ -21,7 +30,7 @@ impl Shape for Sphere {
         point - Tuple::point(0., 0., 0.)
     }
 
-    fn local_intersect(&self, ray: Ray) -> Option<Intersections> {
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
         let sphere_origin = Tuple::point(0., 0., 0.);
         let sphere_to_ray = ray.origin - sphere_origin;
 
@@ -35,21 +44,21 @@ impl Shape for Sphere {
             return None;
         }
 
-        let t1 = (-b - discriminant.sqrt()) / (2. * a);
-        let t2 = (-b + discriminant.sqrt()) / (2. * a);
+        // a is always positive, so t1 <= t2 and no reordering is needed
+        let root = discriminant.sqrt();
+        let t1 = (-b - root) / (2. * a);
+        let t2 = (-b + root) / (2. * a);
 
-        let mut xs = Intersections::new();
-
-        if t1 < t2 {
-            xs.push(Intersection::new(t1, self));
-            xs.push(Intersection::new(t2, self));
-        } else {
-            xs.push(Intersection::new(t2, self));
-            xs.push(Intersection::new(t1, self));
-        }
+        let mut xs = Intersections::with_capacity(2);
+        xs.push(Intersection::new(t1, self));
+        xs.push(Intersection::new(t2, self));
 
         Some(xs)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Tuple::point(-1., -1., -1.), Tuple::point(1., 1., 1.))
+    }
 }
 
 impl PartialEq for Sphere {
@@ -67,9 +76,7 @@ impl Sphere {
 
     pub(crate) fn glass() -> Sphere {
         let mut s = Sphere::new();
-
-        s.mut_props().set_material_transparency(1.0);
-        s.mut_props().set_material_refractive_index(1.5);
+        s.make_glass();
 
         s
     }
@@ -79,7 +86,11 @@ impl Sphere {
 mod tests {
     use super::*;
     use crate::math::matrix::M4;
+    use crate::math::transformation::translation;
     use crate::math::tuple::Tuple;
+    use crate::primitives::shape::ClipPlane;
+    use crate::scene::shading::color::Color;
+    use crate::scene::shading::material::Material;
     use crate::scene::tracing::ray::Ray;
 
     #[test]
@@ -104,6 +115,18 @@ mod tests {
         assert_eq!(5., xs.get(1).unwrap().t);
     }
 
+    #[test]
+    fn test_ray_intersect_scaled_direction_keeps_order() {
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 2.));
+        let s = Sphere::new();
+
+        let xs = s.local_intersect(r).unwrap();
+
+        assert_eq!(2, xs.len());
+        assert_eq!(2., xs.get(0).unwrap().t);
+        assert_eq!(3., xs.get(1).unwrap().t);
+    }
+
     #[test]
     fn test_ray_misses_sphere() {
         let r = Ray::new(Tuple::point(0., 2., -5.), Tuple::direction(0., 0., 1.));
@@ -193,5 +216,47 @@ mod tests {
         assert_eq!(s.get_props().get_transform(), M4::identity());
         assert_eq!(s.get_props().get_material().get_transparency(), 1.0);
         assert_eq!(s.get_props().get_material().get_refractive_index(), 1.5);
+
+        let mut glass = Material::new();
+        glass.set_transparency(1.0);
+        glass.set_refractive_index(1.5);
+        assert_eq!(s.get_props().get_material(), &glass);
+    }
+
+    #[test]
+    fn test_clip_plane_through_center_keeps_one_hemisphere() {
+        let mut s = Sphere::new();
+        s.mut_props().add_clip_plane(ClipPlane::new(
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 0., -1.),
+        ));
+
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let xs = s.intersect(r).unwrap();
+        assert_eq!(1, xs.len());
+        assert_eq!(4., xs.get(0).unwrap().t);
+
+        let r = Ray::new(Tuple::point(0., -5., 0.5), Tuple::direction(0., 1., 0.));
+        assert!(s.intersect(r).is_none());
+
+        let r = Ray::new(Tuple::point(0., -5., -0.5), Tuple::direction(0., 1., 0.));
+        assert_eq!(2, s.intersect(r).unwrap().len());
+    }
+
+    #[test]
+    fn test_cloned_sphere_box_is_a_new_object_with_the_same_transform() {
+        let mut s = Sphere::new();
+        s.mut_props().set_transform(translation(1., 2., 3.));
+        s.mut_props().set_material_color(Color::new(0.2, 0.4, 0.6));
+        let original: Box<dyn Shape> = Box::new(s);
+
+        let copy = original.clone();
+
+        assert_eq!(
+            original.get_props().get_transform(),
+            copy.get_props().get_transform()
+        );
+        assert_eq!(original.material(), copy.material());
+        assert_ne!(original.get_id(), copy.get_id());
     }
 }
@@ -0,0 +1,323 @@
+use crate::math::tuple::Tuple;
+use crate::math::EPSILON;
+use crate::primitives::bounding_box::BoundingBox;
+use crate::primitives::shape::{Shape, ShapeKind, ShapeProps};
+use crate::scene::tracing::intersection::{Intersection, Intersections};
+use crate::scene::tracing::ray::Ray;
+
+const BISECTION_STEPS: usize = 100;
+
+// ring around the y axis, the major radius is the distance from the y axis to the center of
+// the tube, the minor radius the radius of the tube itself
+#[derive(Clone, Debug)]
+pub(crate) struct Torus {
+    props: ShapeProps,
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Torus {
+    pub(crate) fn new(major_radius: f64, minor_radius: f64) -> Torus {
+        Torus {
+            props: ShapeProps::default(),
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Shape for Torus {
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> Option<ShapeKind> {
+        Some(ShapeKind::Torus {
+            major_radius: self.major_radius,
+            minor_radius: self.minor_radius,
+        })
+    }
+
+    fn get_props(&self) -> &ShapeProps {
+        &self.props
+    }
+
+    fn mut_props(&mut self) -> &mut ShapeProps {
+        &mut self.props
+    }
+
+    // gradient of (x² + y² + z² + R² - r²)² - 4R²(x² + z²)
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let major_sqr = self.major_radius.powi(2);
+        let minor_sqr = self.minor_radius.powi(2);
+        let sum = point.x.powi(2) + point.y.powi(2) + point.z.powi(2);
+
+        Tuple::direction(
+            point.x * (sum - major_sqr - minor_sqr),
+            point.y * (sum + major_sqr - minor_sqr),
+            point.z * (sum - major_sqr - minor_sqr),
+        )
+        .normalize()
+    }
+
+    fn local_intersect(&self, ray: Ray) -> Option<Intersections<'_>> {
+        // solved along the normalized direction, the coefficients of heavily scaled tori
+        // would otherwise span too many orders of magnitude
+        let length = ray.direction.magnitude();
+        let o = ray.origin;
+        let d = ray.direction * (1. / length);
+        let four_major_sqr = 4. * self.major_radius.powi(2);
+
+        // inserting the ray into the implicit equation gives a quartic in the distance
+        let m = d.x.powi(2) + d.y.powi(2) + d.z.powi(2);
+        let f = o.x * d.x + o.y * d.y + o.z * d.z;
+        let g = o.x.powi(2) + o.y.powi(2) + o.z.powi(2) + self.major_radius.powi(2)
+            - self.minor_radius.powi(2);
+
+        let roots = real_roots(&[
+            g.powi(2) - four_major_sqr * (o.x.powi(2) + o.z.powi(2)),
+            4. * f * g - 2. * four_major_sqr * (o.x * d.x + o.z * d.z),
+            2. * m * g + 4. * f.powi(2) - four_major_sqr * (d.x.powi(2) + d.z.powi(2)),
+            4. * m * f,
+            m.powi(2),
+        ]);
+
+        if roots.is_empty() {
+            return None;
+        }
+
+        let mut xs = Intersections::new();
+        for distance in roots {
+            xs.push(Intersection::new(distance / length, self));
+        }
+
+        Some(xs)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let outer = self.major_radius + self.minor_radius;
+
+        BoundingBox::new(
+            Tuple::point(-outer, -self.minor_radius, -outer),
+            Tuple::point(outer, self.minor_radius, outer),
+        )
+    }
+}
+
+impl PartialEq for Torus {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+// ascending real roots of the polynomial with the given coefficients, lowest degree first.
+// The extrema (roots of the derivative) split the polynomial into monotone pieces, every piece
+// with a sign change holds exactly one root, found by bisection. An extremum touching zero is
+// a double root and listed twice, like a ray grazing a sphere.
+fn real_roots(coefficients: &[f64]) -> Vec<f64> {
+    // leading coefficients negligible next to the largest one don't raise the degree
+    let largest = coefficients.iter().map(|c| c.abs()).fold(0., f64::max);
+    let mut coefficients = coefficients.to_vec();
+    while coefficients.len() > 1
+        && coefficients.last().unwrap().abs() <= largest * EPSILON * EPSILON
+    {
+        coefficients.pop();
+    }
+
+    let degree = coefficients.len() - 1;
+    if degree == 0 {
+        return vec![];
+    }
+    if degree == 1 {
+        return vec![-coefficients[0] / coefficients[1]];
+    }
+
+    let derivative: Vec<f64> = coefficients
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(power, c)| c * power as f64)
+        .collect();
+    let mut extrema = real_roots(&derivative);
+    extrema.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+    // no root is further away from zero than this (Cauchy bound)
+    let leading = coefficients[degree];
+    let bound = 1.
+        + coefficients[..degree]
+            .iter()
+            .map(|c| (c / leading).abs())
+            .fold(0., f64::max);
+
+    let mut roots = vec![];
+    let mut points = vec![(-bound, false)];
+    for &extremum in &extrema {
+        let touches_zero = is_root(&coefficients, extremum);
+        if touches_zero {
+            roots.push(extremum);
+            roots.push(extremum);
+        }
+        points.push((extremum, touches_zero));
+    }
+    points.push((bound, false));
+
+    for pair in points.windows(2) {
+        let ((low, low_is_root), (high, high_is_root)) = (pair[0], pair[1]);
+        if low_is_root || high_is_root {
+            continue;
+        }
+        if evaluate(&coefficients, low) * evaluate(&coefficients, high) < 0. {
+            roots.push(bisect(&coefficients, low, high));
+        }
+    }
+
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots
+}
+
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0., |result, c| result * x + c)
+}
+
+// zero relative to the magnitude of the summed terms, absorbs rounding errors at double roots
+fn is_root(coefficients: &[f64], x: f64) -> bool {
+    let magnitude = coefficients
+        .iter()
+        .rev()
+        .fold(0., |result, c| result * x.abs() + c.abs());
+
+    evaluate(coefficients, x).abs() <= magnitude * EPSILON * EPSILON
+}
+
+fn bisect(coefficients: &[f64], mut low: f64, mut high: f64) -> f64 {
+    let low_is_negative = evaluate(coefficients, low) < 0.;
+
+    for _ in 0..BISECTION_STEPS {
+        let middle = (low + high) / 2.;
+        if (evaluate(coefficients, middle) < 0.) == low_is_negative {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::f64_eq;
+    use crate::math::transformation::scaling;
+
+    fn assert_hits(torus: &Torus, origin: Tuple, direction: Tuple, expected: &[f64]) {
+        let r = Ray::new(origin, direction);
+
+        let xs = torus.local_intersect(r);
+
+        let actual: Vec<f64> = match xs {
+            Some(xs) => (0..xs.len()).map(|i| xs.get(i).unwrap().t).collect(),
+            None => vec![],
+        };
+        assert_eq!(expected.len(), actual.len(), "{:?}", actual);
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert!(f64_eq(*e, *a), "expected {} but got {}", e, a);
+        }
+    }
+
+    #[test]
+    fn test_head_on_ray_intersects_torus_four_times() {
+        let torus = Torus::new(1., 0.25);
+
+        assert_hits(
+            &torus,
+            Tuple::point(-5., 0., 0.),
+            Tuple::direction(1., 0., 0.),
+            &[3.75, 4.25, 5.75, 6.25],
+        );
+    }
+
+    #[test]
+    fn test_heavily_scaled_torus_still_has_four_hits() {
+        for scale in &[0.001, 1000.] {
+            let mut torus = Torus::new(1., 0.25);
+            torus.set_transform(scaling(*scale, *scale, *scale));
+            let r = Ray::new(
+                Tuple::point(-5. * scale, 0., 0.),
+                Tuple::direction(1., 0., 0.),
+            );
+
+            let xs = torus.intersect(r).unwrap();
+
+            assert_eq!(4, xs.len());
+            for (i, expected) in [3.75, 4.25, 5.75, 6.25].iter().enumerate() {
+                let t = xs.get(i).unwrap().t;
+                assert!(f64_eq(*expected, t / scale), "{} at scale {}", t, scale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ray_through_hole_of_torus() {
+        let torus = Torus::new(1., 0.25);
+
+        assert_hits(
+            &torus,
+            Tuple::point(0., 5., 0.),
+            Tuple::direction(0., -1., 0.),
+            &[],
+        );
+        assert_hits(
+            &torus,
+            Tuple::point(1., 5., 0.),
+            Tuple::direction(0., -1., 0.),
+            &[4.75, 5.25],
+        );
+    }
+
+    #[test]
+    fn test_tangent_ray_grazes_top_of_torus() {
+        let torus = Torus::new(1., 0.25);
+
+        assert_hits(
+            &torus,
+            Tuple::point(-5., 0.25, 0.),
+            Tuple::direction(1., 0., 0.),
+            &[4., 4., 6., 6.],
+        );
+    }
+
+    #[test]
+    fn test_normal_on_torus() {
+        let torus = Torus::new(1., 0.25);
+        let cases = [
+            (Tuple::point(1.25, 0., 0.), Tuple::direction(1., 0., 0.)),
+            (Tuple::point(0.75, 0., 0.), Tuple::direction(-1., 0., 0.)),
+            (Tuple::point(1., 0.25, 0.), Tuple::direction(0., 1., 0.)),
+            (Tuple::point(0., -0.25, 1.), Tuple::direction(0., -1., 0.)),
+            (Tuple::point(0., 0., -1.25), Tuple::direction(0., 0., -1.)),
+        ];
+
+        for (point, normal) in cases.iter() {
+            assert_eq!(*normal, torus.local_normal_at(*point));
+        }
+    }
+
+    #[test]
+    fn test_real_roots_of_polynomials() {
+        // (x - 1)(x - 2)(x + 3) = x³ - 7x + 6
+        let roots = real_roots(&[6., -7., 0., 1.]);
+        assert_eq!(3, roots.len());
+        assert!(f64_eq(-3., roots[0]) && f64_eq(1., roots[1]) && f64_eq(2., roots[2]));
+
+        assert!(real_roots(&[1., 0., 1.]).is_empty());
+
+        // a tiny leading coefficient still counts when all coefficients are tiny
+        let roots = real_roots(&[2e-12, -3e-12, 1e-12]);
+        assert_eq!(2, roots.len());
+        assert!(f64_eq(1., roots[0]) && f64_eq(2., roots[1]));
+    }
+}
@@ -4,7 +4,7 @@ use crate::pattern::checker::CheckerPattern;
 use crate::primitives::plane::Plane;
 use crate::primitives::shape::Shape;
 use crate::primitives::sphere::Sphere;
-use crate::samples::utility::{date_ymd_his, save, SceneCamera};
+use crate::samples::utility::{date_ymd_his, print_progress, save, SceneCamera};
 use crate::scene::shading::color::Color;
 use crate::scene::shading::light::PointLight;
 use crate::scene::world::World;
@@ -16,7 +16,7 @@ use std::time::Instant;
 pub fn _run() {
     // WORLD SETUP
     let mut world = World::new();
-    world.light = Some(PointLight::new(Tuple::point(-5., 5., -5.), Color::white()));
+    world.add_light(PointLight::new(Tuple::point(-5., 5., -5.), Color::white()));
 
     // SCENE POPULATION
     let mut floor = Plane::new();
@@ -26,7 +26,7 @@ pub fn _run() {
         Color::new(0.3, 0.3, 0.3),
         Color::new(0.7, 0.7, 0.7),
     )));
-    world.objects.push(Box::new(floor));
+    world.add_object(floor);
 
     let mut back_wall = Plane::new();
     back_wall
@@ -35,7 +35,7 @@ pub fn _run() {
     back_wall
         .mut_props()
         .set_material_color(Color::new(0.2, 0.2, 0.2));
-    world.objects.push(Box::new(back_wall));
+    world.add_object(back_wall);
 
     let mut center_sphere = Sphere::glass();
     center_sphere
@@ -46,7 +46,7 @@ pub fn _run() {
     center_sphere.mut_props().set_material_reflective(0.9);
     center_sphere.mut_props().set_material_specular(1.);
     center_sphere.mut_props()._set_material_shininess(300.);
-    world.objects.push(Box::new(center_sphere));
+    world.add_object(center_sphere);
 
     let mut left_behind_sphere = Sphere::new();
     left_behind_sphere
@@ -55,7 +55,7 @@ pub fn _run() {
     left_behind_sphere
         .mut_props()
         .set_material_color(Color::new(1., 0., 0.));
-    world.objects.push(Box::new(left_behind_sphere));
+    world.add_object(left_behind_sphere);
 
     let mut right_behind_sphere = Sphere::new();
     right_behind_sphere
@@ -64,7 +64,7 @@ pub fn _run() {
     right_behind_sphere
         .mut_props()
         .set_material_color(Color::new(0., 0., 1.));
-    world.objects.push(Box::new(right_behind_sphere));
+    world.add_object(right_behind_sphere);
 
     let mut left_before_sphere = Sphere::glass();
     left_before_sphere
@@ -78,7 +78,7 @@ pub fn _run() {
     left_before_sphere.mut_props().set_material_reflective(0.9);
     left_before_sphere.mut_props().set_material_specular(1.);
     left_before_sphere.mut_props()._set_material_shininess(300.);
-    world.objects.push(Box::new(left_before_sphere));
+    world.add_object(left_before_sphere);
 
     let mut right_before_sphere = Sphere::glass();
     right_before_sphere
@@ -94,7 +94,7 @@ pub fn _run() {
     right_before_sphere
         .mut_props()
         ._set_material_shininess(300.);
-    world.objects.push(Box::new(right_before_sphere));
+    world.add_object(right_before_sphere);
 
     // CAMERA SETUP
     let camera = SceneCamera::new(Tuple::point(0., 2.0, -8.), Tuple::point(0., 0., 0.));
@@ -104,7 +104,7 @@ pub fn _run() {
     let start = Instant::now();
     println!("start tracing");
 
-    let canvas = camera.get_camera().render_multi_threaded(w);
+    let canvas = camera.get_camera().render_with_progress(w, print_progress);
     save("refraction", canvas, 1, &date);
 
     println!(
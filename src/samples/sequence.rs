@@ -10,7 +10,7 @@ use crate::pattern::Pattern;
 use crate::primitives::plane::Plane;
 use crate::primitives::shape::Shape;
 use crate::primitives::sphere::Sphere;
-use crate::samples::utility::{date_ymd_his, save, SceneCamera};
+use crate::samples::utility::{date_ymd_his, print_progress, save, SceneCamera};
 use crate::scene::world::World;
 
 use std::f64::consts::PI;
@@ -19,12 +19,12 @@ use std::time::Instant;
 
 pub fn _run() {
     let mut world = World::new();
-    world.light = Some(PointLight::new(Tuple::point(0., 5., -10.), Color::white()));
+    world.add_light(PointLight::new(Tuple::point(0., 5., -10.), Color::white()));
 
     let mut center_sphere = Sphere::new();
     center_sphere.mut_props().set_material_diffuse(0.);
     center_sphere.mut_props().set_material_reflective(1.);
-    world.objects.push(Box::new(center_sphere));
+    world.add_object(center_sphere);
 
     let mut left_sphere = Sphere::new();
     left_sphere
@@ -37,7 +37,7 @@ pub fn _run() {
     let left_sphere_transform =
         translation(-1., -0.5, -1.5) * scaling(0.5, 0.5, 0.5) * rotation_x(0.) * M4::identity();
     left_sphere.mut_props().set_transform(left_sphere_transform);
-    world.objects.push(Box::new(left_sphere));
+    world.add_object(left_sphere);
 
     let mut right_sphere = Sphere::glass();
     // right_sphere
@@ -52,7 +52,7 @@ pub fn _run() {
     right_sphere
         .mut_props()
         .set_transform(right_sphere_transform);
-    world.objects.push(Box::new(right_sphere));
+    world.add_object(right_sphere);
 
     let mut floor = Plane::new();
     floor.mut_props().set_transform(translation(0., -1., 0.));
@@ -61,7 +61,7 @@ pub fn _run() {
         Color::new(0.8, 0.8, 0.8),
     )));
     floor.mut_props().set_material_reflective(0.1);
-    world.objects.push(Box::new(floor));
+    world.add_object(floor);
 
     let mut right_wall = Plane::new();
     // right_wall
@@ -70,10 +70,10 @@ pub fn _run() {
     //         Color::new(0.0, 0.5, 0.0),
     //         Color::new(0.8, 0.8, 0.8),
     //     )));
-    let mut gradient_pattern =
-        GradientPattern::new(Color::new(0.0, 0.5, 0.0), Color::new(0.8, 0.8, 0.8));
     let asd_transform = translation(-1., 0., 0.) * scaling(5., 5., 5.) * M4::identity();
-    gradient_pattern.mut_props().set_transform(asd_transform);
+    let gradient_pattern =
+        GradientPattern::new(Color::new(0.0, 0.5, 0.0), Color::new(0.8, 0.8, 0.8))
+            .with_transform(asd_transform);
     right_wall
         .mut_props()
         .set_pattern(Box::new(gradient_pattern));
@@ -81,7 +81,7 @@ pub fn _run() {
     let right_wall_transform =
         translation(5., 0., 0.) * rotation_y(PI * 1.75) * rotation_z(PI / 2.) * M4::identity();
     right_wall.mut_props().set_transform(right_wall_transform);
-    world.objects.push(Box::new(right_wall));
+    world.add_object(right_wall);
 
     let mut left_wall = Plane::new();
     left_wall
@@ -94,7 +94,7 @@ pub fn _run() {
     let left_wall_transform =
         translation(-5., 0., 0.) * rotation_y(PI * 0.25) * rotation_z(PI / 2.) * M4::identity();
     left_wall.mut_props().set_transform(left_wall_transform);
-    world.objects.push(Box::new(left_wall));
+    world.add_object(left_wall);
 
     let mut camera = SceneCamera::new(Tuple::point(0., 2., -5.), Tuple::point(0., 0., 0.));
     let date = date_ymd_his();
@@ -107,7 +107,7 @@ pub fn _run() {
         camera._move_camera(0., 0.1, 0.);
 
         let w = w.clone();
-        let canvas = camera.get_camera().render_multi_threaded(w);
+        let canvas = camera.get_camera().render_with_progress(w, print_progress);
         save("sequence", canvas, foo + 1, &date);
     }
 
@@ -3,6 +3,7 @@ use crate::math::tuple::Tuple;
 use crate::primitives::shape::ShapeProps;
 use crate::scene::camera::Camera;
 use crate::scene::canvas::Canvas;
+use crate::scene::shading::material::MaterialBuilder;
 use chrono::{Datelike, Local, Timelike};
 use std::f64::consts::PI;
 
@@ -16,31 +17,49 @@ const SURFACE_HEIGHT: usize = 240;
 // const SURFACE_HEIGHT: usize = 24;
 
 pub(super) fn mirror_material(props: &mut ShapeProps) {
-    props.set_material_diffuse(0.1);
-    props.set_material_ambient(0.1);
-    props.set_material_reflective(1.0);
-    props._set_material_shininess(300.);
-    props.set_material_specular(1.);
+    props.set_material(
+        MaterialBuilder::new()
+            .diffuse(0.1)
+            .ambient(0.1)
+            .reflective(1.0)
+            .shininess(300.)
+            .specular(1.)
+            .build(),
+    );
 }
 
 pub(super) fn glass_material(props: &mut ShapeProps) {
-    props.set_material_diffuse(0.1);
-    props.set_material_ambient(0.1);
-    props.set_material_reflective(0.9);
-    props.set_material_transparency(1.0);
-    props.set_material_refractive_index(1.5);
-    props._set_material_shininess(300.);
-    props.set_material_specular(1.);
+    props.set_material(
+        MaterialBuilder::new()
+            .diffuse(0.1)
+            .ambient(0.1)
+            .reflective(0.9)
+            .transparency(1.0)
+            .refractive_index(1.5)
+            .shininess(300.)
+            .specular(1.)
+            .build(),
+    );
 }
 
 pub(super) fn water_material(props: &mut ShapeProps) {
-    props.set_material_diffuse(0.1);
-    props.set_material_ambient(0.1);
-    props.set_material_reflective(0.9);
-    props.set_material_transparency(1.0);
-    props.set_material_refractive_index(1.33);
-    props._set_material_shininess(15.);
-    props.set_material_specular(0.8);
+    props.set_material(
+        MaterialBuilder::new()
+            .diffuse(0.1)
+            .ambient(0.1)
+            .reflective(0.9)
+            .transparency(1.0)
+            .refractive_index(1.33)
+            .shininess(15.)
+            .specular(0.8)
+            .build(),
+    );
+}
+
+pub(super) fn print_progress(done: usize, total: usize) {
+    let percent = (done as f64 / total as f64) * 100.;
+
+    println!("processed {} pixels from {} ({:.2}%)", done, total, percent)
 }
 
 pub(super) fn save(name: &str, canvas: Canvas, image: usize, date: &str) {
@@ -102,11 +121,11 @@ impl SceneCamera {
 
     pub(super) fn get_camera(&self) -> Camera {
         let mut c = Camera::new(SURFACE_WIDTH, SURFACE_HEIGHT, PI / 3.);
-        c.transform = view_transform(
+        c.set_transform(view_transform(
             Tuple::point(self.transform.x, self.transform.y, self.transform.z),
             Tuple::point(self.look_at.x, self.look_at.y, self.look_at.z),
             Tuple::direction(0., 1., 0.),
-        );
+        ));
 
         c
     }
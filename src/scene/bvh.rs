@@ -0,0 +1,149 @@
+use crate::primitives::bounding_box::{axis_of, BoundingBox};
+use crate::primitives::shape::Shape;
+use crate::scene::tracing::ray::Ray;
+use std::cmp::Ordering;
+
+const MAX_LEAF_SIZE: usize = 2;
+
+// bounding volume hierarchy over indices into a list of objects. Objects without finite
+// bounds (e.g. planes) can't be sorted into boxes and are always tested.
+#[derive(Clone, Debug)]
+pub(crate) struct Bvh {
+    root: Option<Node>,
+    unbounded: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Leaf {
+        bounds: BoundingBox,
+        objects: Vec<usize>,
+    },
+    Branch {
+        bounds: BoundingBox,
+        left: Box<Node>,
+        right: Box<Node>,
+    },
+}
+
+impl Bvh {
+    pub(crate) fn build(objects: &[Box<dyn Shape>]) -> Bvh {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+
+        for (index, object) in objects.iter().enumerate() {
+            let bounds = object.world_bounds();
+            if bounds.is_finite() {
+                bounded.push((index, bounds));
+            } else {
+                unbounded.push(index);
+            }
+        }
+
+        let root = if bounded.is_empty() {
+            None
+        } else {
+            Some(Node::build(bounded))
+        };
+
+        Bvh {
+            root,
+            unbounded,
+            len: objects.len(),
+        }
+    }
+
+    // number of objects the hierarchy was built for
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    // indices of all objects the ray might hit
+    pub(crate) fn candidates(&self, ray: Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+
+        if let Some(root) = &self.root {
+            root.collect(ray, &mut result);
+        }
+
+        result
+    }
+}
+
+impl Node {
+    fn build(mut objects: Vec<(usize, BoundingBox)>) -> Node {
+        let mut bounds = BoundingBox::empty();
+        for (_, object_bounds) in &objects {
+            bounds.merge(*object_bounds);
+        }
+
+        if objects.len() <= MAX_LEAF_SIZE {
+            return Node::Leaf {
+                bounds,
+                objects: objects.iter().map(|(index, _)| *index).collect(),
+            };
+        }
+
+        // split at the median of the object centers along the longest axis
+        let axis = bounds.longest_axis();
+        let center = |b: &BoundingBox| -> f64 { axis_of(b.centroid(), axis) };
+        objects
+            .sort_by(|(_, a), (_, b)| center(a).partial_cmp(&center(b)).unwrap_or(Ordering::Equal));
+        let right = objects.split_off(objects.len() / 2);
+
+        Node::Branch {
+            bounds,
+            left: Box::new(Node::build(objects)),
+            right: Box::new(Node::build(right)),
+        }
+    }
+
+    fn collect(&self, ray: Ray, result: &mut Vec<usize>) {
+        match self {
+            Node::Leaf { bounds, objects } => {
+                if bounds.intersects(ray) {
+                    result.extend(objects);
+                }
+            }
+            Node::Branch {
+                bounds,
+                left,
+                right,
+            } => {
+                if bounds.intersects(ray) {
+                    left.collect(ray, result);
+                    right.collect(ray, result);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::transformation::translation;
+    use crate::math::tuple::Tuple;
+    use crate::primitives::plane::Plane;
+    use crate::primitives::sphere::Sphere;
+
+    #[test]
+    fn test_candidates_skip_objects_outside_of_ray() {
+        let mut objects: Vec<Box<dyn Shape>> = vec![Box::new(Plane::new())];
+        for x in 0..8 {
+            let mut s = Sphere::new();
+            s.mut_props()
+                .set_transform(translation(x as f64 * 3., 0., 0.));
+            objects.push(Box::new(s));
+        }
+
+        let bvh = Bvh::build(&objects);
+        let r = Ray::new(Tuple::point(6., 0., -5.), Tuple::direction(0., 0., 1.));
+        let mut candidates = bvh.candidates(r);
+        candidates.sort_unstable();
+
+        assert_eq!(9, bvh.len());
+        assert_eq!(vec![0, 3, 4], candidates);
+    }
+}
@@ -1,22 +1,98 @@
 use crate::math::matrix::M4;
 use crate::math::tuple::Tuple;
+use crate::primitives::bounding_box::BoundingBox;
 use crate::scene::canvas::Canvas;
+use crate::scene::sampling::Sequence;
+use crate::scene::shading::color::Color;
 use crate::scene::tracing::ray::Ray;
 use crate::scene::world::World;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 use threadpool::ThreadPool;
 
+const RECURSION_DEPTH: usize = 5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Projection {
+    Perspective,
+    // equirectangular 360° image, x maps to the longitude and y to the latitude
+    Panorama,
+    // parallel rays, starting on a view plane of the given size in world units
+    Orthographic { width: f64, height: f64 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum RenderMode {
+    Shaded,
+    // surface normals of the first hit as colors, each component mapped from [-1, 1] to
+    // [0, 1], black for misses. Useful to debug geometry.
+    Normals,
+    // no shadows, reflections or refractions, see World::color_at_preview
+    Preview,
+}
+
+// only the settings are saved, everything derived from them is computed again when loading
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "SavedCamera")]
 pub(crate) struct Camera {
     h_size: usize,
     v_size: usize,
     fov: f64,
-    pub(crate) transform: M4,
+    transform: M4,
+    // cached, every ray needs it
+    #[serde(skip_serializing)]
+    inverse_transform: M4,
+    pub(crate) projection: Projection,
+    // a debugging aid, it isn't saved
+    #[serde(skip_serializing)]
+    pub(crate) render_mode: RenderMode,
+    // renders only objects whose bounds reach into the view. Culled objects don't cast
+    // shadows or show up in reflections anymore, so it is off by default.
+    #[serde(skip_serializing)]
+    pub(crate) frustum_culling: bool,
+    // rays per pixel, more than one jitters them inside the pixel for anti-aliasing. A render
+    // setting like the mode, it isn't saved either.
+    #[serde(skip_serializing)]
+    pub(crate) samples_per_pixel: usize,
+    #[serde(skip_serializing)]
     pixel_size: f64,
+    #[serde(skip_serializing)]
     half_width: f64,
+    #[serde(skip_serializing)]
     half_height: f64,
 }
 
+#[derive(Deserialize)]
+struct SavedCamera {
+    h_size: usize,
+    v_size: usize,
+    fov: f64,
+    transform: M4,
+    projection: Projection,
+}
+
+impl TryFrom<SavedCamera> for Camera {
+    type Error = String;
+
+    fn try_from(saved: SavedCamera) -> Result<Camera, String> {
+        if saved.transform.inverse().is_none() {
+            return Err(String::from("camera transformation must be invertible"));
+        }
+
+        let mut camera = Camera::new(saved.h_size, saved.v_size, saved.fov);
+        camera.set_transform(saved.transform);
+        camera.projection = saved.projection;
+
+        Ok(camera)
+    }
+}
+
 impl Camera {
     pub(crate) fn new(h_size: usize, v_size: usize, fov: f64) -> Camera {
         let transform = M4::identity();
@@ -37,102 +113,470 @@ impl Camera {
             v_size,
             fov,
             transform,
+            inverse_transform: transform,
+            projection: Projection::Perspective,
+            render_mode: RenderMode::Shaded,
+            frustum_culling: false,
+            samples_per_pixel: 1,
             pixel_size,
             half_width,
             half_height,
         }
     }
 
-    pub(crate) fn _render(&self, world: Arc<World>) -> Canvas {
+    // the field of view in degrees instead of radians
+    pub(crate) fn from_degrees(h_size: usize, v_size: usize, fov_degrees: f64) -> Camera {
+        Camera::new(h_size, v_size, fov_degrees.to_radians())
+    }
+
+    pub(crate) fn get_fov(&self) -> f64 {
+        self.fov
+    }
+
+    pub(crate) fn fov_degrees(&self) -> f64 {
+        self.fov.to_degrees()
+    }
+
+    pub(crate) fn get_transform(&self) -> M4 {
+        self.transform
+    }
+
+    pub(crate) fn get_inverse_transform(&self) -> M4 {
+        self.inverse_transform
+    }
+
+    pub(crate) fn set_transform(&mut self, transform: M4) {
+        self.inverse_transform = transform
+            .inverse()
+            .expect("camera transformation must be invertible");
+        self.transform = transform;
+    }
+
+    // single threaded and renders the pixels in a fixed order, useful for debugging
+    pub(crate) fn render(&self, world: Arc<World>) -> Canvas {
+        self.render_region(world, 0, 0, self.h_size, self.v_size)
+    }
+
+    // renders only the tile from (x0, y0) up to, but excluding, (x1, y1), the rest of the
+    // image stays black. Tiles rendered separately, e.g. on other machines, are stitched by
+    // cropping them and copying them into place with Canvas::blit.
+    pub(crate) fn render_region(
+        &self,
+        world: Arc<World>,
+        x0: usize,
+        y0: usize,
+        x1: usize,
+        y1: usize,
+    ) -> Canvas {
+        let visible = self.cull(&world);
+        let mut canvas = Canvas::new(self.h_size, self.v_size);
+
+        for y in y0..y1.min(self.v_size) {
+            for x in x0..x1.min(self.h_size) {
+                let color = self.pixel_color(&world, visible.as_deref(), x, y, self.render_mode);
+                canvas.write_pixel(x, y, color);
+            }
+        }
+
+        canvas
+    }
+
+    // single threaded like render, but in the much cheaper preview mode
+    pub(crate) fn render_preview(&self, world: Arc<World>) -> Canvas {
+        let visible = self.cull(&world);
         let mut canvas = Canvas::new(self.h_size, self.v_size);
 
         for y in 0..self.v_size {
             for x in 0..self.h_size {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(ray, 5);
-                canvas.write_pixel(x, y, color);
+                canvas.write_pixel(
+                    x,
+                    y,
+                    self.pixel_color(&world, visible.as_deref(), x, y, RenderMode::Preview),
+                );
             }
         }
 
         canvas
     }
 
+    // distance along the ray to the first hit per pixel row by row, infinity for misses
+    pub(crate) fn render_depth(&self, world: &World) -> Vec<f64> {
+        let mut rays = Vec::with_capacity(self.h_size * self.v_size);
+        for y in 0..self.v_size {
+            for x in 0..self.h_size {
+                rays.push(self.ray_for_pixel(x, y));
+            }
+        }
+
+        world
+            .depth_prepass(&rays)
+            .into_iter()
+            .map(|depth| depth.unwrap_or(f64::INFINITY))
+            .collect()
+    }
+
     pub(crate) fn render_multi_threaded(&self, world: Arc<World>) -> Canvas {
+        self.render_with_progress(world, |_, _| {})
+    }
+
+    // multi threaded rendering, on_progress receives the rendered and the total pixel count
+    // whenever a stripe is finished
+    pub(crate) fn render_with_progress(
+        &self,
+        world: Arc<World>,
+        on_progress: impl Fn(usize, usize),
+    ) -> Canvas {
+        let visible = self.cull(&world);
         let mut canvas = Canvas::new(self.h_size, self.v_size);
 
         let (tx, rx) = channel();
-        let pool = ThreadPool::new(num_cpus::get());
+        let workers = num_cpus::get();
+        let pool = ThreadPool::new(workers);
 
-        for y in 0..self.v_size {
-            for x in 0..self.h_size {
-                let ray = self.ray_for_pixel(x, y);
-                let tx = tx.clone();
-                let world = world.clone();
+        // every task renders a stripe of whole rows, which keeps the per task overhead low
+        let rows_per_chunk = (self.v_size / workers).max(1);
+        let mut chunks = 0;
 
-                pool.execute(move || {
-                    let color = world.color_at(ray, 5);
-                    tx.send((x, y, color)).expect("failed to send");
-                });
-            }
+        for start in (0..self.v_size).step_by(rows_per_chunk) {
+            let end = (start + rows_per_chunk).min(self.v_size);
+            let tx = tx.clone();
+            let world = world.clone();
+            let visible = visible.clone();
+            let camera = self.clone();
+
+            pool.execute(move || {
+                let mut colors = Vec::with_capacity((end - start) * camera.h_size);
+                for y in start..end {
+                    for x in 0..camera.h_size {
+                        colors.push(camera.pixel_color(
+                            &world,
+                            visible.as_deref(),
+                            x,
+                            y,
+                            camera.render_mode,
+                        ));
+                    }
+                }
+                tx.send((start, colors)).expect("failed to send");
+            });
+            chunks += 1;
         }
 
         let mut counter = 0;
         let max = self.v_size * self.h_size;
 
-        for _ in 0..max {
-            let (x, y, color) = rx.recv().unwrap();
-            canvas.write_pixel(x, y, color);
+        for _ in 0..chunks {
+            let (start, colors) = rx.recv().unwrap();
 
-            if counter % 5000 == 0 {
-                let percent = (counter as f64 / max as f64) * 100.;
+            for (i, color) in colors.into_iter().enumerate() {
+                canvas.write_pixel(i % self.h_size, start + i / self.h_size, color);
+                counter += 1;
+            }
 
-                println!(
-                    "processed {} pixels from {} ({:.2}%)",
-                    counter, max, percent
-                )
+            on_progress(counter, max);
+        }
+        // wait until every task dropped its world, so the caller owns the Arc alone again
+        pool.join();
+
+        canvas
+    }
+
+    // multi threaded rendering with rayon's work stealing instead of a thread pool and a channel
+    #[cfg(feature = "rayon")]
+    pub(crate) fn render_rayon(&self, world: Arc<World>) -> Canvas {
+        use rayon::prelude::*;
+
+        let visible = self.cull(&world);
+        let mut rows = vec![vec![Color::black(); self.h_size]; self.v_size];
+
+        rows.par_iter_mut().enumerate().for_each(|(y, row)| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = self.pixel_color(&world, visible.as_deref(), x, y, self.render_mode);
             }
+        });
 
-            counter += 1;
+        let mut canvas = Canvas::new(self.h_size, self.v_size);
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, color) in row.into_iter().enumerate() {
+                canvas.write_pixel(x, y, color);
+            }
         }
 
         canvas
     }
 
+    // renders row by row and checkpoints the canvas and a bitmask of the finished pixels after
+    // every row. Started again with the same paths, only the missing pixels are rendered.
+    pub(crate) fn render_resumable(
+        &self,
+        world: Arc<World>,
+        canvas_path: &Path,
+        completed_mask_path: &Path,
+    ) -> io::Result<Canvas> {
+        let visible = self.cull(&world);
+        let (mut canvas, mut completed) = self.load_checkpoint(canvas_path, completed_mask_path);
+
+        for y in 0..self.v_size {
+            let mut rendered = false;
+            for x in 0..self.h_size {
+                let index = y * self.h_size + x;
+                if completed[index] {
+                    continue;
+                }
+
+                canvas.write_pixel(
+                    x,
+                    y,
+                    self.pixel_color(&world, visible.as_deref(), x, y, self.render_mode),
+                );
+                completed[index] = true;
+                rendered = true;
+            }
+
+            if rendered {
+                write_checkpoint(canvas_path, &canvas.to_bytes())?;
+                write_checkpoint(completed_mask_path, &pack_mask(&completed))?;
+            }
+        }
+
+        Ok(canvas)
+    }
+
+    // starts from scratch if there is no usable checkpoint for this camera's size
+    fn load_checkpoint(
+        &self,
+        canvas_path: &Path,
+        completed_mask_path: &Path,
+    ) -> (Canvas, Vec<bool>) {
+        let pixels = self.h_size * self.v_size;
+        let canvas = fs::read(canvas_path)
+            .ok()
+            .and_then(|bytes| Canvas::from_bytes(&bytes))
+            .filter(|c| c.get_width() == self.h_size && c.get_height() == self.v_size);
+        let mask = fs::read(completed_mask_path)
+            .ok()
+            .filter(|bytes| bytes.len() == pixels.div_ceil(8));
+
+        match (canvas, mask) {
+            (Some(canvas), Some(mask)) => (canvas, unpack_mask(&mask, pixels)),
+            _ => (Canvas::new(self.h_size, self.v_size), vec![false; pixels]),
+        }
+    }
+
+    // marks the objects inside of the view frustum for the primary rays, None if culling is
+    // disabled. Objects outside can still show up in reflections and cast shadows into view.
+    fn cull(&self, world: &World) -> Option<Vec<bool>> {
+        if !self.frustum_culling || self.projection != Projection::Perspective {
+            return None;
+        }
+
+        Some(
+            world
+                .objects()
+                .iter()
+                .map(|object| !self.outside_frustum(object.world_bounds()))
+                .collect(),
+        )
+    }
+
+    // conservative test in camera space, which looks toward -z. A box is outside if it lies
+    // completely behind the camera or beyond one of the four side planes, even at its
+    // farthest depth where the frustum is widest.
+    fn outside_frustum(&self, bounds: BoundingBox) -> bool {
+        if !bounds.is_finite() {
+            return false;
+        }
+
+        let bounds = bounds.transform(self.transform);
+        let (min, max) = (bounds.get_min(), bounds.get_max());
+        if min.z >= 0. {
+            return true;
+        }
+
+        let depth = -min.z;
+        min.x > self.half_width * depth
+            || max.x < -self.half_width * depth
+            || min.y > self.half_height * depth
+            || max.y < -self.half_height * depth
+    }
+
+    // averages jittered rays through the pixel when supersampling. The jitter is seeded with
+    // the pixel's index, so a pixel comes out the same whichever thread renders it.
+    fn pixel_color(
+        &self,
+        world: &World,
+        visible: Option<&[bool]>,
+        x: usize,
+        y: usize,
+        mode: RenderMode,
+    ) -> Color {
+        if self.samples_per_pixel <= 1 {
+            return trace(world, visible, self.ray_for_pixel(x, y), mode);
+        }
+
+        let mut sequence = Sequence::new((y * self.h_size + x) as u64);
+        let mut sum = Color::black();
+        for _ in 0..self.samples_per_pixel {
+            let (dx, dy) = sequence.next_offset();
+            let ray = self.ray_through(x as f64 + 0.5 + dx, y as f64 + 0.5 + dy);
+            sum = sum + trace(world, visible, ray, mode);
+        }
+
+        sum * (1. / self.samples_per_pixel as f64)
+    }
+
     fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
-        // the offset from the edge of the canvas to the pixel's center
-        let x_offset = (x as f64 + 0.5) * self.pixel_size;
-        let y_offset = (y as f64 + 0.5) * self.pixel_size;
+        self.ray_through(x as f64 + 0.5, y as f64 + 0.5)
+    }
 
-        // the untransformed coordinates of the pixel in world space.
-        // (remember that the camera looks toward -z, so +x is to the *left*.)
-        let world_x = self.half_width - x_offset;
-        let world_y = self.half_height - y_offset;
+    // ray through the canvas position in pixels, measured from the top left corner
+    fn ray_through(&self, x: f64, y: f64) -> Ray {
+        let inverse_transform = &self.inverse_transform;
+        let eye = inverse_transform * Tuple::point(0., 0., 0.);
 
-        // using the camera matrix, transform the canvas point and the origin,
-        // and then compute the ray's direction vector.
-        // (remember that the canvas is at z=-1)
-        let inverse_transform = self.transform.inverse().unwrap();
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => {
+                // the offset from the edge of the canvas to the position
+                let x_offset = x * self.pixel_size;
+                let y_offset = y * self.pixel_size;
 
-        let pixel = inverse_transform * Tuple::point(world_x, world_y, -1.);
-        let origin = inverse_transform * Tuple::point(0., 0., 0.);
-        let direction = (pixel - origin).normalize();
+                // the untransformed coordinates of the pixel in world space.
+                // (remember that the camera looks toward -z, so +x is to the *left*.)
+                let world_x = self.half_width - x_offset;
+                let world_y = self.half_height - y_offset;
+
+                // using the camera matrix, transform the canvas point and the origin,
+                // and then compute the ray's direction vector.
+                // (remember that the canvas is at z=-1)
+                let pixel = inverse_transform * Tuple::point(world_x, world_y, -1.);
+
+                (eye, (pixel - eye).normalize())
+            }
+            Projection::Panorama => {
+                let u = x / self.h_size as f64;
+                let v = y / self.v_size as f64;
+
+                (
+                    eye,
+                    (inverse_transform * panorama_direction(u, v)).normalize(),
+                )
+            }
+            Projection::Orthographic { width, height } => {
+                let world_x = width / 2. - x * width / self.h_size as f64;
+                let world_y = height / 2. - y * height / self.v_size as f64;
+
+                let origin = inverse_transform * Tuple::point(world_x, world_y, 0.);
+                let forward = inverse_transform * Tuple::direction(0., 0., -1.);
+
+                (origin, forward.normalize())
+            }
+        };
 
         Ray::new(origin, direction)
     }
 }
 
+// both render paths trace with the same recursion depth, so they produce the same image
+fn trace(world: &World, visible: Option<&[bool]>, ray: Ray, mode: RenderMode) -> Color {
+    match mode {
+        RenderMode::Shaded => world.color_at_visible(ray, RECURSION_DEPTH, visible),
+        RenderMode::Preview => world.color_at_preview(ray, visible),
+        RenderMode::Normals => match world.hit_normal(ray, visible) {
+            Some(n) => Color::new((n.x + 1.) / 2., (n.y + 1.) / 2., (n.z + 1.) / 2.),
+            None => Color::black(),
+        },
+    }
+}
+
+// written next to the checkpoint and renamed over it, so an interrupted write can't leave a
+// truncated checkpoint behind
+fn write_checkpoint(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, bytes)?;
+
+    fs::rename(&temp, path)
+}
+
+// eight pixels per byte, the first pixel in the lowest bit
+fn pack_mask(completed: &[bool]) -> Vec<u8> {
+    completed
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (bit, done)| byte | ((*done as u8) << bit))
+        })
+        .collect()
+}
+
+fn unpack_mask(bytes: &[u8], pixels: usize) -> Vec<bool> {
+    (0..pixels)
+        .map(|i| bytes[i / 8] & (1 << (i % 8)) != 0)
+        .collect()
+}
+
+// maps u, v in [0, 1] to a direction on the unit sphere. u = 0.5 and v = 0.5 look along -z,
+// u = 0 and u = 1 both look backwards, v = 0 looks straight up.
+fn panorama_direction(u: f64, v: f64) -> Tuple {
+    let longitude = (u - 0.5) * 2. * PI;
+    let latitude = (0.5 - v) * PI;
+
+    Tuple::direction(
+        -longitude.sin() * latitude.cos(),
+        latitude.sin(),
+        -longitude.cos() * latitude.cos(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::math::f64_eq;
     use crate::math::transformation::{rotation_y, scaling, translation, view_transform};
     use crate::math::tuple::Tuple;
+    use crate::math::{f64_eq, EPSILON};
     use crate::primitives::shape::Shape;
+    use crate::primitives::shape::ShapeProps;
     use crate::primitives::sphere::Sphere;
-    use crate::scene::shading::color::Color;
     use crate::scene::shading::light::PointLight;
+    use crate::scene::tracing::intersection::Intersections;
     use crate::scene::world::World;
-    use std::f64::consts::PI;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // unit box which counts how often it is tested, but is never hit
+    #[derive(Clone, Debug)]
+    struct CountingShape {
+        props: ShapeProps,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Shape for CountingShape {
+        fn clone_box(&self) -> Box<dyn Shape> {
+            Box::new(self.clone())
+        }
+
+        fn get_props(&self) -> &ShapeProps {
+            &self.props
+        }
+
+        fn mut_props(&mut self) -> &mut ShapeProps {
+            &mut self.props
+        }
+
+        fn local_normal_at(&self, point: Tuple) -> Tuple {
+            point
+        }
+
+        fn local_intersect(&self, _: Ray) -> Option<Intersections<'_>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            None
+        }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::new(Tuple::point(-1., -1., -1.), Tuple::point(1., 1., 1.))
+        }
+    }
 
     #[test]
     fn test_create_camera() {
@@ -145,7 +589,18 @@ mod tests {
         assert_eq!(160, c.h_size);
         assert_eq!(120, c.v_size);
         assert_eq!(PI / 2., c.fov);
-        assert_eq!(M4::identity(), c.transform);
+        assert_eq!(M4::identity(), c.get_transform());
+    }
+
+    #[test]
+    fn test_set_transform_caches_inverse() {
+        let mut c = Camera::new(11, 11, PI / 2.);
+        let transform = rotation_y(PI / 4.) * translation(0., -2., 5.);
+
+        c.set_transform(transform);
+
+        assert_eq!(transform, c.get_transform());
+        assert_eq!(transform.inverse().unwrap(), c.get_inverse_transform());
     }
 
     #[test]
@@ -155,6 +610,17 @@ mod tests {
         assert!(f64_eq(c.pixel_size, 0.01));
     }
 
+    #[test]
+    fn test_camera_from_degrees_equals_camera_from_radians() {
+        let c = Camera::from_degrees(100, 50, 90.);
+        let e = Camera::new(100, 50, PI / 2.);
+
+        assert!(f64_eq(e.pixel_size, c.pixel_size));
+        assert!(f64_eq(e.half_width, c.half_width));
+        assert!(f64_eq(e.half_height, c.half_height));
+        assert!(f64_eq(90., c.fov_degrees()));
+    }
+
     #[test]
     fn test_pixel_size_for_vertical_canvas() {
         let c = Camera::new(125, 200, PI / 2.);
@@ -183,7 +649,7 @@ mod tests {
     #[test]
     fn test_create_ray_then_camera_is_transformed() {
         let mut c = Camera::new(201, 101, PI / 2.);
-        c.transform = rotation_y(PI / 4.) * translation(0., -2., 5.);
+        c.set_transform(rotation_y(PI / 4.) * translation(0., -2., 5.));
 
         let r = c.ray_for_pixel(100, 50);
 
@@ -196,30 +662,415 @@ mod tests {
 
     #[test]
     fn test_render_world_with_camera() {
-        let point_light = PointLight::new(Tuple::point(-10., 10., -10.), Color::new(1., 1., 1.));
-        let mut s1 = Sphere::new();
-        s1.mut_props().set_material_color(Color::new(0.8, 1., 0.6));
-        s1.mut_props().set_material_diffuse(0.7);
-        s1.mut_props().set_material_specular(0.2);
+        let w = World::demo();
 
-        let mut s2 = Sphere::new();
-        s2.mut_props().set_transform(scaling(0.5, 0.5, 0.5));
+        let mut c = Camera::new(11, 11, PI / 2.);
+        let from = Tuple::point(0., 0., -5.);
+        let to = Tuple::point(0., 0., 0.);
+        let up = Tuple::direction(0., 1., 0.);
+        c.set_transform(view_transform(from, to, up));
 
-        let mut w = World::new();
-        w.light = Some(point_light);
-        w.objects.push(Box::new(s1));
-        w.objects.push(Box::new(s2));
+        let image = c.render(Arc::new(w));
+        assert_eq!(
+            Color::new(0.38066, 0.47583, 0.2855),
+            image.pixel_at(5, 5).unwrap()
+        );
+    }
 
-        let mut c = Camera::new(11, 11, PI / 2.);
+    #[test]
+    fn test_stitched_quadrant_tiles_match_full_render() {
+        let w = Arc::new(World::demo());
+
+        let mut c = Camera::new(11, 9, PI / 2.);
         let from = Tuple::point(0., 0., -5.);
         let to = Tuple::point(0., 0., 0.);
         let up = Tuple::direction(0., 1., 0.);
-        c.transform = view_transform(from, to, up);
+        c.set_transform(view_transform(from, to, up));
+
+        let image = c.render(w.clone());
+
+        let mut stitched = Canvas::new(11, 9);
+        for (x0, y0, x1, y1) in &[(0, 0, 6, 5), (6, 0, 11, 5), (0, 5, 6, 9), (6, 5, 11, 9)] {
+            let tile = c.render_region(w.clone(), *x0, *y0, *x1, *y1);
+            assert_eq!(11, tile.get_width());
+            stitched.blit(&tile.crop(*x0, *y0, *x1, *y1), *x0, *y0);
+        }
+
+        assert_eq!(image.to_ppm(), stitched.to_ppm());
+    }
+
+    #[test]
+    fn test_region_leaves_rest_of_image_black() {
+        let w = Arc::new(World::demo());
+        let c = Camera::new(4, 4, PI / 2.);
+        let image = c.render(w.clone());
 
-        let image = c._render(Arc::new(w));
+        let tile = c.render_region(w, 2, 2, 6, 6);
+
+        assert_eq!(4, tile.get_width());
+        assert_eq!(4, tile.get_height());
+        assert_eq!(image.pixel_at(2, 2), tile.pixel_at(2, 2));
+        assert_eq!(Color::new(0., 0., 0.), tile.pixel_at(1, 1).unwrap());
+        assert_eq!(Color::new(0., 0., 0.), tile.pixel_at(3, 1).unwrap());
+    }
+
+    #[test]
+    fn test_stats_count_one_primary_ray_per_pixel() {
+        let mut w = World::demo();
+        w.enable_stats();
+        let w = Arc::new(w);
+
+        let c = Camera::new(7, 5, PI / 2.);
+        c.render(w.clone());
+
+        assert_eq!(7 * 5, w.stats().primary_rays);
+    }
+
+    #[test]
+    fn test_preview_of_mirror_shows_base_color_without_reflections() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(Tuple::point(0., 10., 0.), Color::white()));
+        let mut mirror = Sphere::new();
+        mirror
+            .mut_props()
+            .set_material_color(Color::new(0.2, 0.2, 0.2));
+        mirror.mut_props().set_material_reflective(1.);
+        w.add_object(mirror);
+        let mut red = Sphere::new();
+        red.mut_props().set_material_color(Color::new(1., 0., 0.));
+        red.mut_props().set_transform(translation(0., 0., -5.));
+        w.add_object(red);
+        let w = Arc::new(w);
+
+        let mut c = Camera::new(5, 5, PI / 6.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -2.5),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        // the center pixel sees the mirror reflecting the red sphere behind the camera
+        let full = c.render(w.clone()).pixel_at(2, 2).unwrap();
+        let preview = c.render_preview(w).pixel_at(2, 2).unwrap();
+
+        assert!(full.red > full.green + 0.1);
+        assert!(f64_eq(preview.red, preview.green));
+        assert!(f64_eq(preview.green, preview.blue));
+    }
+
+    #[test]
+    fn test_normals_mode_colors_the_normal_facing_the_camera() {
+        let mut w = World::new();
+        w.add_object(Sphere::new());
+
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+        c.render_mode = RenderMode::Normals;
+
+        let image = c.render(Arc::new(w));
+        assert_eq!(Color::new(0.5, 0.5, 0.), image.pixel_at(5, 5).unwrap());
+        assert_eq!(Color::black(), image.pixel_at(0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_depth_render_of_sphere_ahead() {
+        let mut w = World::new();
+        w.add_object(Sphere::new());
+
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        let depth = c.render_depth(&w);
+        let at = |x: usize, y: usize| depth[y * 11 + x];
+
+        assert_eq!(121, depth.len());
+        assert!(f64_eq(4., at(5, 5)));
+        assert!(at(5, 5) < at(4, 5));
+        assert!(at(4, 5).is_finite());
+        assert_eq!(f64::INFINITY, at(0, 0));
+        assert_eq!(f64::INFINITY, at(0, 5));
+    }
+
+    #[test]
+    fn test_frustum_culling_skips_objects_behind_the_camera_for_primary_rays() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut behind = CountingShape {
+            props: ShapeProps::default(),
+            calls: calls.clone(),
+        };
+        behind.set_transform(translation(0., 0., -20.));
+        let mut w = World::demo();
+        w.add_object(behind);
+        let w = Arc::new(w);
+
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        let full = c.render(w.clone());
+        let without_culling = calls.swap(0, Ordering::SeqCst);
+        c.frustum_culling = true;
+        let image = c.render(w);
+
+        // shadow rays still test the object, only the 11 * 11 primary rays skip it
+        assert_eq!(without_culling - 121, calls.load(Ordering::SeqCst));
+        assert_eq!(full.to_ppm(), image.to_ppm());
         assert_eq!(
             Color::new(0.38066, 0.47583, 0.2855),
             image.pixel_at(5, 5).unwrap()
         );
     }
+
+    #[test]
+    fn test_frustum_keeps_visible_and_infinite_bounds() {
+        let mut c = Camera::new(100, 50, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+        let unit = BoundingBox::new(Tuple::point(-1., -1., -1.), Tuple::point(1., 1., 1.));
+
+        assert!(!c.outside_frustum(unit));
+        assert!(!c.outside_frustum(BoundingBox::infinite()));
+        assert!(c.outside_frustum(unit.transform(translation(0., 0., -10.))));
+        assert!(c.outside_frustum(unit.transform(translation(20., 0., 0.))));
+        assert!(c.outside_frustum(unit.transform(translation(0., 10., 5.))));
+        assert!(!c.outside_frustum(unit.transform(translation(0., 2., 5.))));
+    }
+
+    #[test]
+    fn test_panorama_center_pixel_looks_forward() {
+        let mut c = Camera::new(101, 51, PI / 2.);
+        c.projection = Projection::Panorama;
+
+        let r = c.ray_for_pixel(50, 25);
+
+        assert_eq!(r.origin, Tuple::point(0., 0., 0.));
+        assert_eq!(r.direction, Tuple::direction(0., 0., -1.));
+    }
+
+    #[test]
+    fn test_panorama_left_and_right_edges_wrap_around() {
+        assert_eq!(panorama_direction(0., 0.5), panorama_direction(1., 0.5));
+        assert_eq!(Tuple::direction(0., 0., 1.), panorama_direction(0., 0.5));
+        assert_eq!(Tuple::direction(0., 1., 0.), panorama_direction(0.5, 0.));
+
+        let mut c = Camera::new(100, 50, PI / 2.);
+        c.projection = Projection::Panorama;
+        let left = c.ray_for_pixel(0, 25);
+        let right = c.ray_for_pixel(99, 25);
+
+        // the outermost pixels are neighbours across the seam
+        assert!(left.direction.dot(right.direction) > (2. * PI / 100.).cos() - EPSILON);
+        assert!(left.direction.z > 0.99 && right.direction.z > 0.99);
+    }
+
+    #[test]
+    fn test_multi_threaded_render_matches_single_threaded_render() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Tuple::point(-10., 10., -10.),
+            Color::white(),
+        ));
+        let mut s1 = Sphere::new();
+        s1.mut_props().set_material_color(Color::new(0.8, 1., 0.6));
+        w.add_object(s1);
+        let mut s2 = Sphere::new();
+        s2.mut_props()
+            .set_transform(translation(1., 0.5, -1.) * scaling(0.5, 0.5, 0.5));
+        w.add_object(s2);
+        let w = Arc::new(w);
+
+        let mut c = Camera::new(23, 17, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        let single = c.render(w.clone());
+        let multi = c.render_multi_threaded(w);
+
+        for y in 0..17 {
+            for x in 0..23 {
+                assert_eq!(single.pixel_at(x, y), multi.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_supersampling_is_reproducible_and_smooths_edges() {
+        let w = Arc::new(World::demo());
+        let mut c = Camera::new(23, 17, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+        let aliased = c.render(w.clone());
+
+        c.samples_per_pixel = 16;
+        let single = c.render(w.clone());
+        let multi = c.render_multi_threaded(w);
+
+        let mut blended = 0;
+        for y in 0..17 {
+            for x in 0..23 {
+                assert_eq!(single.pixel_at(x, y), multi.pixel_at(x, y));
+                if single.pixel_at(x, y) != aliased.pixel_at(x, y) {
+                    blended += 1;
+                }
+            }
+        }
+        // jittered rays blend the outline of the sphere with the background
+        assert!(blended > 0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rayon_render_matches_single_threaded_render() {
+        let w = Arc::new(World::demo());
+
+        let mut c = Camera::new(23, 17, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+
+        let single = c.render(w.clone());
+        let parallel = c.render_rayon(w);
+
+        for y in 0..17 {
+            for x in 0..23 {
+                assert_eq!(single.pixel_at(x, y), parallel.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_with_progress_reports_every_pixel() {
+        let w = Arc::new(World::new());
+        let c = Camera::new(13, 9, PI / 2.);
+        let calls = std::cell::RefCell::new(vec![]);
+
+        c.render_with_progress(w, |done, total| calls.borrow_mut().push((done, total)));
+
+        let calls = calls.into_inner();
+        assert!(!calls.is_empty());
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(Some(&(13 * 9, 13 * 9)), calls.last());
+    }
+
+    #[test]
+    fn test_orthographic_rays_are_parallel() {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        c.projection = Projection::Orthographic {
+            width: 4.,
+            height: 2.,
+        };
+
+        let center = c.ray_for_pixel(100, 50);
+        let corner = c.ray_for_pixel(0, 0);
+
+        assert_eq!(Tuple::direction(0., 0., -1.), center.direction);
+        assert_eq!(center.direction, corner.direction);
+        assert_eq!(Tuple::point(0., 0., 0.), center.origin);
+        assert_eq!(
+            Tuple::direction(2. - 2. / 201., 1. - 1. / 101., 0.),
+            corner.origin - center.origin
+        );
+    }
+
+    #[test]
+    fn test_orthographic_rays_of_transformed_camera() {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        c.projection = Projection::Orthographic {
+            width: 4.,
+            height: 2.,
+        };
+        c.set_transform(rotation_y(PI / 2.) * translation(0., -2., 5.));
+
+        let center = c.ray_for_pixel(100, 50);
+        let corner = c.ray_for_pixel(0, 0);
+
+        assert_eq!(Tuple::point(0., 2., -5.), center.origin);
+        assert_eq!(Tuple::direction(1., 0., 0.), center.direction);
+        assert_eq!(center.direction, corner.direction);
+    }
+
+    #[test]
+    fn test_resumed_render_only_renders_missing_pixels() {
+        let w = Arc::new(World::demo());
+        let mut c = Camera::new(11, 9, PI / 2.);
+        c.set_transform(view_transform(
+            Tuple::point(0., 0., -5.),
+            Tuple::point(0., 0., 0.),
+            Tuple::direction(0., 1., 0.),
+        ));
+        let full = c.render(w.clone());
+
+        let directory = std::env::temp_dir();
+        let canvas_path = directory.join(format!("{}.canvas", uuid::Uuid::new_v4()));
+        let mask_path = directory.join(format!("{}.mask", uuid::Uuid::new_v4()));
+
+        // the first four rows are done, marked with a color the renderer never produces
+        let marker = Color::new(-1., -1., -1.);
+        let mut partial = Canvas::new(11, 9);
+        let mut completed = vec![false; 11 * 9];
+        for y in 0..4 {
+            for x in 0..11 {
+                partial.write_pixel(x, y, marker);
+                completed[y * 11 + x] = true;
+            }
+        }
+        fs::write(&canvas_path, partial.to_bytes()).unwrap();
+        fs::write(&mask_path, pack_mask(&completed)).unwrap();
+
+        let resumed = c
+            .render_resumable(w.clone(), &canvas_path, &mask_path)
+            .unwrap();
+
+        for y in 0..9 {
+            for x in 0..11 {
+                let expected = if y < 4 {
+                    Some(marker)
+                } else {
+                    full.pixel_at(x, y)
+                };
+                assert_eq!(expected, resumed.pixel_at(x, y));
+            }
+        }
+        assert_eq!(
+            vec![true; 11 * 9],
+            unpack_mask(&fs::read(&mask_path).unwrap(), 11 * 9)
+        );
+
+        // the checkpoint of a finished render resumes to the same image without rendering
+        fs::remove_file(&canvas_path).unwrap();
+        let fresh = c
+            .render_resumable(w.clone(), &canvas_path, &mask_path)
+            .unwrap();
+        let again = c.render_resumable(w, &canvas_path, &mask_path).unwrap();
+        assert_eq!(full.hash(), fresh.hash());
+        assert_eq!(full.hash(), again.hash());
+        // the checkpoints were renamed into place, no temporary files are left
+        assert!(!Path::new(&format!("{}.tmp", canvas_path.display())).exists());
+        assert!(!Path::new(&format!("{}.tmp", mask_path.display())).exists());
+
+        fs::remove_file(&canvas_path).unwrap();
+        fs::remove_file(&mask_path).unwrap();
+    }
 }
@@ -1,10 +1,27 @@
 use crate::scene::shading::color::Color;
 
+// from dark to bright, used for terminal previews
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+// ordered dithering thresholds, in sixteenths
+const BAYER_4X4: [[f64; 4]; 4] = [
+    [0., 8., 2., 10.],
+    [12., 4., 14., 6.],
+    [3., 11., 1., 9.],
+    [15., 7., 13., 5.],
+];
+
+// 64 bit FNV-1a parameters for the region hashes
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
 #[derive(Debug)]
 pub(crate) struct Canvas {
     width: usize,
     height: usize,
     pixels: Vec<Vec<Color>>,
+    // applied when writing the image, the pixels stay linear
+    gamma: f64,
 }
 
 impl Canvas {
@@ -16,11 +33,33 @@ impl Canvas {
             width,
             height,
             pixels,
+            gamma: 1.,
         }
     }
 
+    // None for a canvas without pixels, Canvas::new allows them and writes an empty image
+    pub(crate) fn try_new(width: usize, height: usize) -> Option<Canvas> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(Canvas::new(width, height))
+    }
+
+    pub(crate) fn set_gamma(&mut self, gamma: f64) {
+        self.gamma = gamma;
+    }
+
+    pub(crate) fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub(crate) fn get_height(&self) -> usize {
+        self.height
+    }
+
     pub(crate) fn pixel_at(&self, width: usize, height: usize) -> Option<Color> {
-        if width > self.width || height > self.height {
+        if width >= self.width || height >= self.height {
             return None;
         }
 
@@ -28,14 +67,275 @@ impl Canvas {
     }
 
     pub(crate) fn write_pixel(&mut self, width: usize, height: usize, color: Color) {
-        if width > self.width || height > self.height {
+        if width >= self.width || height >= self.height {
             return;
         }
 
         self.pixels[height][width] = color;
     }
 
+    pub(crate) fn fill(&mut self, color: Color) {
+        for row in self.pixels.iter_mut() {
+            row.fill(color);
+        }
+    }
+
+    // copies the other canvas with its top left corner at the offset, clipping what doesn't fit
+    pub(crate) fn blit(&mut self, other: &Canvas, x_off: usize, y_off: usize) {
+        for y in 0..other.height {
+            for x in 0..other.width {
+                self.write_pixel(x + x_off, y + y_off, other.pixels[y][x]);
+            }
+        }
+    }
+
+    // copy of the pixels from (x0, y0) up to, but excluding, (x1, y1), clipped to the canvas
+    pub(crate) fn crop(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> Canvas {
+        let (x1, y1) = (x1.min(self.width), y1.min(self.height));
+        let mut canvas = Canvas::new(x1.saturating_sub(x0), y1.saturating_sub(y0));
+        for y in y0..y1 {
+            for x in x0..x1 {
+                canvas.write_pixel(x - x0, y - y0, self.pixels[y][x]);
+            }
+        }
+
+        canvas
+    }
+
     pub(crate) fn to_ppm(&self) -> String {
+        self.ppm(false)
+    }
+
+    // like to_ppm, but with ordered dithering to reduce banding in smooth gradients
+    pub(crate) fn to_ppm_dithered(&self) -> String {
+        self.ppm(true)
+    }
+
+    // downsampled preview for the terminal, characters are about twice as tall as wide, so
+    // every character covers a block of pixels twice as high as wide
+    pub(crate) fn to_ascii(&self, width_chars: usize) -> String {
+        if self.width == 0 || self.height == 0 {
+            return String::new();
+        }
+
+        let width_chars = width_chars.clamp(1, self.width.max(1));
+        let block = self.width as f64 / width_chars as f64;
+        let height_chars = ((self.height as f64 / (block * 2.)).round() as usize).max(1);
+
+        let mut result = String::new();
+        for row in 0..height_chars {
+            let y_start = row * self.height / height_chars;
+            let y_end = ((row + 1) * self.height / height_chars).max(y_start + 1);
+
+            for column in 0..width_chars {
+                let x_start = column * self.width / width_chars;
+                let x_end = ((column + 1) * self.width / width_chars).max(x_start + 1);
+
+                let mut luminance = 0.;
+                let mut count = 0;
+                for y in y_start..y_end.min(self.height) {
+                    for x in x_start..x_end.min(self.width) {
+                        luminance += luminance_of(self.pixels[y][x].clamp());
+                        count += 1;
+                    }
+                }
+                if count > 0 {
+                    luminance /= count as f64;
+                }
+
+                let index = (luminance * (ASCII_RAMP.len() - 1) as f64).round() as usize;
+                result.push(ASCII_RAMP[index] as char);
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+
+    // compresses bright highlights with c / (1 + c) per channel instead of clipping them,
+    // so details above 1 survive the conversion to bytes. Dims the whole image a bit.
+    pub(crate) fn tone_map_reinhard(&self) -> Canvas {
+        let map = |channel: f64| {
+            let channel = channel.max(0.);
+            channel / (1. + channel)
+        };
+
+        let mut result = Canvas::new(self.width, self.height);
+        result.gamma = self.gamma;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.pixels[y][x];
+                result.pixels[y][x] = Color::new(map(pixel.red), map(pixel.green), map(pixel.blue));
+            }
+        }
+
+        result
+    }
+
+    // glow around bright areas: everything brighter than the threshold is blurred and added
+    // back on top, scaled by the intensity
+    pub(crate) fn bloom(&mut self, threshold: f64, intensity: f64, radius: usize) {
+        let mut bright = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.pixels[y][x];
+                if luminance_of(pixel) > threshold {
+                    bright.pixels[y][x] = pixel;
+                }
+            }
+        }
+
+        let glow = bright.box_blur(radius);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.pixels[y][x] = self.pixels[y][x] + glow.pixels[y][x] * intensity;
+            }
+        }
+    }
+
+    // lens color fringing: the red channel is magnified and the blue channel shrunk around the
+    // image center, so both separate at edges, more so towards the border
+    pub(crate) fn chromatic_aberration(&mut self, strength: f64) {
+        let center_x = self.width as f64 / 2.;
+        let center_y = self.height as f64 / 2.;
+        let mut pixels = self.pixels.clone();
+
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let offset_x = x as f64 + 0.5 - center_x;
+                let offset_y = y as f64 + 0.5 - center_y;
+                let sample_at = |scale: f64| {
+                    self.sample(center_x + offset_x * scale, center_y + offset_y * scale)
+                };
+
+                pixel.red = sample_at(1. / (1. + strength)).red;
+                pixel.blue = sample_at(1. + strength).blue;
+            }
+        }
+
+        self.pixels = pixels;
+    }
+
+    // bilinear interpolation between the four nearest pixel centers, clamped at the border
+    fn sample(&self, x: f64, y: f64) -> Color {
+        let x = (x - 0.5).clamp(0., (self.width - 1) as f64);
+        let y = (y - 0.5).clamp(0., (self.height - 1) as f64);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+
+        let top = self.pixels[y0][x0].lerp(self.pixels[y0][x1], fx);
+        let bottom = self.pixels[y1][x0].lerp(self.pixels[y1][x1], fx);
+
+        top.lerp(bottom, fy)
+    }
+
+    // averages every pixel with its neighbours up to radius pixels away, done horizontally and
+    // then vertically. Near the border only the pixels inside the canvas are averaged.
+    fn box_blur(&self, radius: usize) -> Canvas {
+        let mut horizontal = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let from = x.saturating_sub(radius);
+                let to = (x + radius).min(self.width - 1);
+                horizontal.pixels[y][x] = Color::average(self.pixels[y][from..=to].iter().copied());
+            }
+        }
+
+        let mut result = Canvas::new(self.width, self.height);
+        for y in 0..self.height {
+            let from = y.saturating_sub(radius);
+            let to = (y + radius).min(self.height - 1);
+            for x in 0..self.width {
+                result.pixels[y][x] =
+                    Color::average((from..=to).map(|row| horizontal.pixels[row][x]));
+            }
+        }
+
+        result
+    }
+
+    // lossless binary form for render checkpoints: width and height followed by the color
+    // channels of every pixel, row by row, all little endian
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.width * self.height * 24);
+        bytes.extend_from_slice(&(self.width as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u64).to_le_bytes());
+        for row in &self.pixels {
+            for pixel in row {
+                for channel in &[pixel.red, pixel.green, pixel.blue] {
+                    bytes.extend_from_slice(&channel.to_le_bytes());
+                }
+            }
+        }
+
+        bytes
+    }
+
+    // None if the bytes weren't written by to_bytes
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Canvas> {
+        let read_u64 = |index: usize| -> Option<u64> {
+            let mut buffer = [0; 8];
+            buffer.copy_from_slice(bytes.get(index * 8..(index + 1) * 8)?);
+            Some(u64::from_le_bytes(buffer))
+        };
+
+        let width = read_u64(0)? as usize;
+        let height = read_u64(1)? as usize;
+        if bytes.len()
+            != width
+                .checked_mul(height)?
+                .checked_mul(24)?
+                .checked_add(16)?
+        {
+            return None;
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let index = 2 + (y * width + x) * 3;
+                let channel = |offset: usize| f64::from_bits(read_u64(index + offset).unwrap());
+                canvas.pixels[y][x] = Color::new(channel(0), channel(1), channel(2));
+            }
+        }
+
+        Some(canvas)
+    }
+
+    pub(crate) fn hash(&self) -> u64 {
+        self.region_hash(0, 0, self.width, self.height)
+    }
+
+    // FNV-1a over the 8 bit pixels of the rectangle from (x0, y0) up to, but excluding,
+    // (x1, y1). Doesn't depend on the rust version, so tiles rendered on different machines
+    // can be compared.
+    pub(crate) fn region_hash(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> u64 {
+        let x1 = x1.min(self.width);
+        let y1 = y1.min(self.height);
+
+        let mut hash = FNV_OFFSET;
+        let mut feed = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        };
+
+        for size in &[x1.saturating_sub(x0) as u64, y1.saturating_sub(y0) as u64] {
+            size.to_le_bytes().iter().for_each(|byte| feed(*byte));
+        }
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (red, green, blue) = self.pixels[y][x].to_rgb8();
+                feed(red);
+                feed(green);
+                feed(blue);
+            }
+        }
+
+        hash
+    }
+
+    fn ppm(&self, dither: bool) -> String {
         let mut header = format!("P3\n{} {}\n255", self.width, self.height);
         let mut data = String::new();
 
@@ -43,9 +343,14 @@ impl Canvas {
             let mut row = String::from("\n");
 
             for w in 0..self.width {
-                let pixel = self.pixel_at(w, h).unwrap();
-
-                let red = Canvas::color_byte_string(pixel.red);
+                let pixel = self.pixel_at(w, h).unwrap().gamma_corrected(self.gamma);
+                let (red, green, blue) = if dither {
+                    Canvas::dithered_rgb8(pixel, w, h)
+                } else {
+                    pixel.to_rgb8()
+                };
+
+                let red = red.to_string();
                 if row.len() + red.len() > 70 {
                     data.push_str(&row.trim_end());
                     row = String::from("\n");
@@ -53,7 +358,7 @@ impl Canvas {
                 row.push_str(&red);
                 row.push_str(" ");
 
-                let green = Canvas::color_byte_string(pixel.green);
+                let green = green.to_string();
                 if row.len() + green.len() > 70 {
                     data.push_str(&row.trim_end());
                     row = String::from("\n");
@@ -61,7 +366,7 @@ impl Canvas {
                 row.push_str(&green);
                 row.push_str(" ");
 
-                let blue = Canvas::color_byte_string(pixel.blue);
+                let blue = blue.to_string();
                 if row.len() + blue.len() > 70 {
                     data.push_str(&row.trim_end());
                     row = String::from("\n");
@@ -79,11 +384,19 @@ impl Canvas {
         header
     }
 
-    fn color_byte_string(color: f64) -> String {
-        (color.clamp(0., 1.) * 255.).round().to_string()
+    fn dithered_rgb8(color: Color, x: usize, y: usize) -> (u8, u8, u8) {
+        let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.;
+        let color = color.clamp();
+        let byte = |channel: f64| (channel * 255. + threshold).floor().min(255.) as u8;
+
+        (byte(color.red), byte(color.green), byte(color.blue))
     }
 }
 
+fn luminance_of(color: Color) -> f64 {
+    0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +429,65 @@ mod tests {
         assert_eq!(red, c.pixel_at(2, 3).unwrap())
     }
 
+    #[test]
+    fn test_blit_copies_canvas_at_offset_and_clips() {
+        let mut c = Canvas::new(4, 3);
+        let mut tile = Canvas::new(2, 2);
+        let red = Color::new(1., 0., 0.);
+        tile.fill(red);
+
+        c.blit(&tile, 3, 1);
+
+        assert_eq!(red, c.pixel_at(3, 1).unwrap());
+        assert_eq!(red, c.pixel_at(3, 2).unwrap());
+        assert_eq!(Color::new(0., 0., 0.), c.pixel_at(2, 1).unwrap());
+        assert_eq!(Color::new(0., 0., 0.), c.pixel_at(3, 0).unwrap());
+    }
+
+    #[test]
+    fn test_crop_copies_region_and_clips() {
+        let mut c = Canvas::new(4, 3);
+        let red = Color::new(1., 0., 0.);
+        c.write_pixel(2, 1, red);
+
+        let cropped = c.crop(2, 1, 6, 6);
+
+        assert_eq!(2, cropped.get_width());
+        assert_eq!(2, cropped.get_height());
+        assert_eq!(red, cropped.pixel_at(0, 0).unwrap());
+        assert_eq!(0, c.crop(5, 0, 6, 3).get_width());
+    }
+
+    #[test]
+    fn test_fill_canvas() {
+        let mut c = Canvas::new(10, 20);
+        let red = Color::new(1., 0., 0.);
+
+        c.fill(red);
+
+        assert_eq!(10, c.get_width());
+        assert_eq!(20, c.get_height());
+        for w in 0..10 {
+            for h in 0..20 {
+                assert_eq!(red, c.pixel_at(w, h).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_pixel_out_of_bounds() {
+        let mut c = Canvas::new(10, 20);
+
+        assert_eq!(None, c.pixel_at(10, 0));
+        assert_eq!(None, c.pixel_at(0, 20));
+
+        c.write_pixel(10, 0, Color::white());
+        c.write_pixel(0, 20, Color::white());
+
+        assert_eq!(Color::black(), c.pixel_at(9, 0).unwrap());
+        assert_eq!(Color::black(), c.pixel_at(0, 19).unwrap());
+    }
+
     #[test]
     fn test_create_ppm_header() {
         let c = Canvas::new(5, 3);
@@ -148,6 +520,29 @@ mod tests {
         assert_eq!("0 0 0 0 0 0 0 0 0 0 0 0 0 0 255", lines[5]);
     }
 
+    #[test]
+    fn test_gamma_brightens_mid_gray_in_ppm() {
+        let mut c = Canvas::new(1, 1);
+        c.write_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+
+        assert_eq!("128 128 128", c.to_ppm().split("\n").nth(3).unwrap());
+
+        c.set_gamma(2.2);
+        let ppm = c.to_ppm();
+        let red: u8 = ppm
+            .split("\n")
+            .nth(3)
+            .unwrap()
+            .split(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert!(red > 128);
+        assert_eq!(Color::new(0.5, 0.5, 0.5), c.pixel_at(0, 0).unwrap());
+    }
+
     #[test]
     fn test_splitting_to_long_ppm_lines() {
         let mut c = Canvas::new(10, 2);
@@ -179,6 +574,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zero_sized_canvas_writes_empty_ppm() {
+        let c = Canvas::new(0, 3);
+
+        assert_eq!("P3\n0 3\n255\n", c.to_ppm());
+        assert_eq!("P3\n5 0\n255\n", Canvas::new(5, 0).to_ppm());
+        assert_eq!("", c.to_ascii(10));
+    }
+
+    #[test]
+    fn test_try_new_rejects_zero_dimensions() {
+        assert!(Canvas::try_new(0, 3).is_none());
+        assert!(Canvas::try_new(3, 0).is_none());
+        assert_eq!(3, Canvas::try_new(3, 2).unwrap().get_width());
+    }
+
     #[test]
     fn test_ppm_ends_with_newlines() {
         let c = Canvas::new(5, 3);
@@ -186,4 +597,180 @@ mod tests {
 
         assert!(ppm.ends_with("\n"));
     }
+
+    #[test]
+    fn test_dithering_breaks_up_banding_of_smooth_gradient() {
+        let width = 16;
+        let mut c = Canvas::new(width, 1);
+        for x in 0..width {
+            let value = (0.6 + 0.8 * x as f64 / (width - 1) as f64) / 255.;
+            c.write_pixel(x, 0, Color::new(value, value, value));
+        }
+
+        let unique_reds = |ppm: String| {
+            let mut reds: Vec<String> = ppm
+                .split_whitespace()
+                .skip(4)
+                .step_by(3)
+                .map(String::from)
+                .collect();
+            reds.sort();
+            reds.dedup();
+            reds.len()
+        };
+
+        let plain = unique_reds(c.to_ppm());
+        let dithered = unique_reds(c.to_ppm_dithered());
+
+        assert_eq!(1, plain);
+        assert!(dithered > plain);
+    }
+
+    #[test]
+    fn test_ascii_preview_of_white_and_black_canvas() {
+        let mut white = Canvas::new(40, 20);
+        for y in 0..20 {
+            for x in 0..40 {
+                white.write_pixel(x, y, Color::white());
+            }
+        }
+        let black = Canvas::new(40, 20);
+
+        let ascii = white.to_ascii(10);
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(3, lines.len());
+        assert!(lines.iter().all(|line| *line == "@@@@@@@@@@"));
+
+        let ascii = black.to_ascii(10);
+        assert!(ascii.lines().all(|line| line == "          "));
+    }
+
+    #[test]
+    fn test_region_hash_only_changes_for_modified_region() {
+        let mut a = Canvas::new(8, 8);
+        let mut b = Canvas::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                let c = Color::new(x as f64 / 8., y as f64 / 8., 0.5);
+                a.write_pixel(x, y, c);
+                b.write_pixel(x, y, c);
+            }
+        }
+        let tiles = [(0, 0, 4, 4), (4, 0, 8, 4), (0, 4, 4, 8), (4, 4, 8, 8)];
+
+        for &(x0, y0, x1, y1) in tiles.iter() {
+            assert_eq!(a.region_hash(x0, y0, x1, y1), b.region_hash(x0, y0, x1, y1));
+        }
+        assert_eq!(a.hash(), b.hash());
+        assert_eq!(a.hash(), a.region_hash(0, 0, 100, 100));
+
+        b.write_pixel(5, 1, Color::white());
+
+        for &(x0, y0, x1, y1) in tiles.iter() {
+            let changed = x0 == 4 && y0 == 0;
+            assert_eq!(
+                changed,
+                a.region_hash(x0, y0, x1, y1) != b.region_hash(x0, y0, x1, y1)
+            );
+        }
+        assert_ne!(a.hash(), b.hash());
+    }
+
+    #[test]
+    fn test_reinhard_tone_mapping_keeps_highlights_below_white() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(4., 1., 0.));
+
+        let mapped = c.tone_map_reinhard();
+
+        assert_eq!((255, 255, 0), c.pixel_at(0, 0).unwrap().to_rgb8());
+        assert_eq!((204, 128, 0), mapped.pixel_at(0, 0).unwrap().to_rgb8());
+        assert_eq!(Color::black(), mapped.pixel_at(1, 0).unwrap());
+    }
+
+    #[test]
+    fn test_bloom_adds_halo_around_bright_pixel() {
+        let mut c = Canvas::new(9, 9);
+        c.write_pixel(4, 4, Color::new(10., 10., 10.));
+
+        c.bloom(1., 1., 2);
+
+        assert!(c.pixel_at(4, 4).unwrap().red > 10.);
+        assert!(c.pixel_at(5, 4).unwrap().red > 0.);
+        assert!(c.pixel_at(6, 6).unwrap().red > 0.);
+        assert_eq!(Color::black(), c.pixel_at(7, 4).unwrap());
+        assert_eq!(c.pixel_at(3, 4), c.pixel_at(5, 4));
+    }
+
+    #[test]
+    fn test_bloom_keeps_dim_image_unchanged() {
+        let mut c = Canvas::new(6, 4);
+        for y in 0..4 {
+            for x in 0..6 {
+                c.write_pixel(x, y, Color::new(0.1 * x as f64, 0.2, 0.1 * y as f64));
+            }
+        }
+        let before = c.hash();
+
+        c.bloom(0.9, 1., 2);
+
+        assert_eq!(before, c.hash());
+        assert_eq!(Color::new(0.5, 0.2, 0.3), c.pixel_at(5, 3).unwrap());
+    }
+
+    #[test]
+    fn test_chromatic_aberration_without_strength_keeps_canvas() {
+        let mut c = Canvas::new(7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                c.write_pixel(x, y, Color::new(0.1 * x as f64, 0.2 * y as f64, 0.3));
+            }
+        }
+        let before = c.hash();
+
+        c.chromatic_aberration(0.);
+
+        assert_eq!(before, c.hash());
+    }
+
+    #[test]
+    fn test_chromatic_aberration_separates_red_and_blue_at_edge() {
+        let mut c = Canvas::new(21, 5);
+        for y in 0..5 {
+            for x in 15..21 {
+                c.write_pixel(x, y, Color::white());
+            }
+        }
+
+        c.chromatic_aberration(0.2);
+
+        // blue reaches over the edge towards the center, red stays behind it
+        let fringe = c.pixel_at(14, 2).unwrap();
+        assert!(fringe.blue > 0.5);
+        assert!(fringe.red < 0.1);
+        assert_eq!(0., fringe.green);
+        assert_eq!(Color::white(), c.pixel_at(18, 2).unwrap());
+        assert_eq!(Color::black(), c.pixel_at(5, 2).unwrap());
+    }
+
+    #[test]
+    fn test_canvas_survives_bytes_round_trip() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(0, 0, Color::new(0.1, 1.5, -0.3));
+        c.write_pixel(2, 1, Color::new(1. / 3., 0., 7.));
+
+        let restored = Canvas::from_bytes(&c.to_bytes()).unwrap();
+
+        assert_eq!(3, restored.get_width());
+        assert_eq!(2, restored.get_height());
+        for y in 0..2 {
+            for x in 0..3 {
+                let (a, b) = (c.pixel_at(x, y).unwrap(), restored.pixel_at(x, y).unwrap());
+                assert_eq!(a.red.to_bits(), b.red.to_bits());
+                assert_eq!(a.green.to_bits(), b.green.to_bits());
+                assert_eq!(a.blue.to_bits(), b.blue.to_bits());
+            }
+        }
+        assert!(Canvas::from_bytes(&c.to_bytes()[..20]).is_none());
+    }
 }
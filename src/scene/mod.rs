@@ -1,5 +1,8 @@
+pub(crate) mod bvh;
 pub(crate) mod camera;
 pub(crate) mod canvas;
+pub(crate) mod sampling;
 pub(crate) mod shading;
+pub(crate) mod sky;
 pub(crate) mod tracing;
 pub(crate) mod world;
@@ -0,0 +1,105 @@
+use crate::math::tuple::Tuple;
+use std::f64::consts::PI;
+
+// seeded source of jitter offsets, the same seed always yields the same sequence so renders
+// with randomized sampling stay reproducible
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Sequence {
+    state: u64,
+}
+
+impl Sequence {
+    pub(crate) fn new(seed: u64) -> Sequence {
+        // xorshift gets stuck on a zero state, scrambling the seed avoids it for seed 0
+        let state = splitmix64(seed);
+
+        Sequence {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    // xorshift64*, returns a number in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let value = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // offset inside a unit square centered on the origin, for jittering samples of a pixel or
+    // an area light
+    pub(crate) fn next_offset(&mut self) -> (f64, f64) {
+        (self.next_f64() - 0.5, self.next_f64() - 0.5)
+    }
+}
+
+// offsets on the unit disk perpendicular to axis, spread by the golden angle so any number
+// of samples covers the disk evenly. The whole spiral is turned by rotation radians.
+pub(crate) fn disk_spiral(axis: Tuple, count: usize, rotation: f64) -> impl Iterator<Item = Tuple> {
+    let helper = if axis.x.abs() < 0.9 {
+        Tuple::direction(1., 0., 0.)
+    } else {
+        Tuple::direction(0., 1., 0.)
+    };
+    let u = axis.cross(helper).normalize();
+    let v = axis.cross(u);
+    let golden_angle = PI * (3. - 5f64.sqrt());
+
+    (0..count).map(move |i| {
+        let radius = ((i as f64 + 0.5) / count as f64).sqrt();
+        let angle = i as f64 * golden_angle + rotation;
+
+        u * (radius * angle.cos()) + v * (radius * angle.sin())
+    })
+}
+
+pub(crate) fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(seed: u64, count: usize) -> Vec<(f64, f64)> {
+        let mut sequence = Sequence::new(seed);
+        (0..count).map(|_| sequence.next_offset()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_yields_same_offsets() {
+        assert_eq!(offsets(42, 32), offsets(42, 32));
+    }
+
+    #[test]
+    fn test_different_seeds_yield_different_offsets() {
+        assert_ne!(offsets(1, 32), offsets(2, 32));
+        assert_ne!(offsets(0, 32), offsets(1, 32));
+    }
+
+    #[test]
+    fn test_offsets_stay_inside_unit_square() {
+        for (x, y) in offsets(0, 1000) {
+            assert!((-0.5..0.5).contains(&x));
+            assert!((-0.5..0.5).contains(&y));
+        }
+    }
+
+    #[test]
+    fn test_disk_spiral_stays_on_the_disk_around_the_axis() {
+        let axis = Tuple::direction(0., 0., 1.);
+        let offsets: Vec<Tuple> = disk_spiral(axis, 16, 1.).collect();
+
+        assert_eq!(16, offsets.len());
+        for offset in offsets {
+            assert!(offset.dot(axis).abs() < 1e-9);
+            assert!(offset.magnitude() <= 1.);
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use crate::math::f64_eq;
+use crate::math::{f64_eq_eps, EPSILON};
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Mul, Sub};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct Color {
     pub(crate) red: f64,
     pub(crate) green: f64,
@@ -20,13 +21,98 @@ impl Color {
     pub(crate) fn black() -> Color {
         Color::new(0., 0., 0.)
     }
+
+    // color of a black body glowing at the temperature in kelvin, from Tanner Helland's fit
+    // of the blackbody curve. Valid from 1000 K to 40000 K, 6600 K is white.
+    pub(crate) fn blackbody(kelvin: f64) -> Color {
+        let t = kelvin.clamp(1000., 40000.) / 100.;
+
+        let red = if t <= 66. {
+            255.
+        } else {
+            329.698_727_446 * (t - 60.).powf(-0.133_204_759_2)
+        };
+        let green = if t <= 66. {
+            99.470_802_586_1 * t.ln() - 161.119_568_166_1
+        } else {
+            288.122_169_528_3 * (t - 60.).powf(-0.075_514_849_2)
+        };
+        let blue = if t >= 66. {
+            255.
+        } else if t <= 19. {
+            0.
+        } else {
+            138.517_731_223_1 * (t - 10.).ln() - 305.044_792_730_7
+        };
+
+        Color::new(red / 255., green / 255., blue / 255.).clamp()
+    }
+
+    pub(crate) fn clamp(&self) -> Color {
+        Color::new(
+            self.red.clamp(0., 1.),
+            self.green.clamp(0., 1.),
+            self.blue.clamp(0., 1.),
+        )
+    }
+
+    // only limits from above, unlike clamp, so negative values from subtracting stay
+    pub(crate) fn clamp_upper(&self, max: f64) -> Color {
+        Color::new(self.red.min(max), self.green.min(max), self.blue.min(max))
+    }
+
+    pub(crate) fn approx_eq(&self, other: Color, eps: f64) -> bool {
+        f64_eq_eps(self.red, other.red, eps)
+            && f64_eq_eps(self.green, other.green, eps)
+            && f64_eq_eps(self.blue, other.blue, eps)
+    }
+
+    pub(crate) fn max_channel(&self) -> f64 {
+        self.red.max(self.green).max(self.blue)
+    }
+
+    // component-wise product, the same as multiplying two colors
+    pub(crate) fn hadamard(self, other: Color) -> Color {
+        self * other
+    }
+
+    // component-wise mean, black without any colors
+    pub(crate) fn average(colors: impl IntoIterator<Item = Color>) -> Color {
+        let (sum, count) = colors
+            .into_iter()
+            .fold((Color::black(), 0), |(sum, count), color| {
+                (sum + color, count + 1)
+            });
+        if count == 0 {
+            return Color::black();
+        }
+
+        sum * (1. / count as f64)
+    }
+
+    pub(crate) fn lerp(&self, other: Color, t: f64) -> Color {
+        *self + (other - *self) * t
+    }
+
+    // clamped to [0, 1] and encoded with the given display gamma, 1 keeps the values linear
+    pub(crate) fn gamma_corrected(&self, gamma: f64) -> Color {
+        let c = self.clamp();
+        let encode = |channel: f64| channel.powf(1. / gamma);
+
+        Color::new(encode(c.red), encode(c.green), encode(c.blue))
+    }
+
+    pub(crate) fn to_rgb8(self) -> (u8, u8, u8) {
+        let c = self.clamp();
+        let byte = |channel: f64| (channel * 255.).round() as u8;
+
+        (byte(c.red), byte(c.green), byte(c.blue))
+    }
 }
 
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        f64_eq(self.red, other.red)
-            && f64_eq(self.green, other.green)
-            && f64_eq(self.blue, other.blue)
+        self.approx_eq(*other, EPSILON)
     }
 }
 
@@ -124,6 +210,35 @@ mod tests {
         assert_eq!(e, r);
     }
 
+    #[test]
+    fn test_hadamard_product_equals_multiplication() {
+        let c1 = Color::new(1., 0.2, 0.4);
+        let c2 = Color::new(0.9, 1., 0.1);
+
+        assert_eq!(c1 * c2, c1.hadamard(c2));
+    }
+
+    #[test]
+    fn test_color_approx_eq_with_looser_tolerance() {
+        let a = Color::new(0.5, 0.5, 0.5);
+        let b = Color::new(0.5004, 0.5, 0.4996);
+
+        assert_ne!(a, b);
+        assert!(a.approx_eq(b, 1e-3));
+    }
+
+    #[test]
+    fn test_max_channel() {
+        assert_eq!(0.9, Color::new(0.2, 0.9, 0.4).max_channel());
+    }
+
+    #[test]
+    fn test_clamp_upper_limits_super_white() {
+        let c = Color::new(2.5, 1.2, 0.4);
+
+        assert_eq!(Color::new(1., 1., 0.4), c.clamp_upper(1.));
+    }
+
     #[test]
     fn test_multiply_color_by_scalar() {
         let c = Color::new(0.2, 0.3, 0.4);
@@ -133,4 +248,63 @@ mod tests {
 
         assert_eq!(e, r);
     }
+
+    #[test]
+    fn test_lerp_between_colors() {
+        let a = Color::new(0.2, 0.4, 1.);
+        let b = Color::new(0.6, 0., 0.5);
+
+        assert_eq!(a, a.lerp(b, 0.));
+        assert_eq!(b, a.lerp(b, 1.));
+        assert_eq!(Color::new(0.4, 0.2, 0.75), a.lerp(b, 0.5));
+    }
+
+    #[test]
+    fn test_average_colors() {
+        let c = Color::new(0.2, 0.4, 0.6);
+
+        assert_eq!(
+            Color::new(0.5, 0.5, 0.5),
+            Color::average(vec![Color::white(), Color::black()])
+        );
+        assert_eq!(c, Color::average(Some(c)));
+        assert_eq!(Color::black(), Color::average(None));
+    }
+
+    #[test]
+    fn test_clamp_color() {
+        let c = Color::new(1.5, -0.5, 0.4).clamp();
+
+        assert_eq!(1.0, c.red);
+        assert_eq!(0.0, c.green);
+        assert_eq!(0.4, c.blue);
+    }
+
+    #[test]
+    fn test_gamma_correction() {
+        let gray = Color::new(0.5, 0.5, 0.5);
+
+        assert_eq!(gray, gray.gamma_corrected(1.));
+        assert_eq!((186, 186, 186), gray.gamma_corrected(2.2).to_rgb8());
+        assert_eq!(Color::white(), Color::new(2., 1., 1.).gamma_corrected(2.2));
+    }
+
+    #[test]
+    fn test_color_to_rgb8() {
+        assert_eq!((255, 0, 128), Color::new(1.5, -0.5, 0.5).to_rgb8());
+        assert_eq!((0, 1, 254), Color::new(0.0019, 0.002, 0.998).to_rgb8());
+    }
+
+    #[test]
+    fn test_blackbody_goes_from_red_over_white_to_blue() {
+        let candle = Color::blackbody(1900.);
+        assert!(candle.red > candle.green && candle.green > candle.blue);
+
+        assert!(Color::blackbody(6600.).approx_eq(Color::white(), 0.01));
+
+        let sky = Color::blackbody(15000.);
+        assert!(sky.blue > sky.green && sky.green > sky.red);
+
+        assert_eq!(Color::blackbody(500.), Color::blackbody(1000.));
+    }
 }
@@ -1,10 +1,101 @@
 use crate::math::tuple::Tuple;
+use crate::scene::sampling::{disk_spiral, splitmix64, Sequence};
 use crate::scene::shading::color::Color;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fmt::Debug;
 
-#[derive(Copy, Clone, Debug)]
+const SHADOW_SAMPLES: usize = 16;
+
+// everything shading needs to know about a light, so other kinds of lights can be added
+// without touching the materials
+pub(crate) trait Light: Debug + Sync + Send {
+    fn position(&self) -> Tuple;
+
+    fn intensity(&self) -> Color;
+
+    fn clone_box(&self) -> Box<dyn Light>;
+
+    // parameters needed to save and rebuild the light, None if it can't be saved
+    fn kind(&self) -> Option<LightKind> {
+        None
+    }
+
+    // passes each point on the light the shadow rays from point are aimed at to sample
+    fn samples(&self, point: Tuple, sample: &mut dyn FnMut(Tuple));
+
+    // fraction of the light reaching the point, from 0 (in shadow) to 1 (fully lit).
+    // is_occluded tells if something blocks the way from point to a sample.
+    fn intensity_at(&self, point: Tuple, is_occluded: &dyn Fn(Tuple) -> bool) -> f64 {
+        let mut total = 0;
+        let mut visible = 0;
+        self.samples(point, &mut |target| {
+            total += 1;
+            if !is_occluded(target) {
+                visible += 1;
+            }
+        });
+
+        visible as f64 / total as f64
+    }
+}
+
+impl Clone for Box<dyn Light> {
+    fn clone(&self) -> Box<dyn Light> {
+        self.clone_box()
+    }
+}
+
+// saved lights are tagged with their type
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum LightKind {
+    Point(PointLight),
+}
+
+impl LightKind {
+    pub(crate) fn build(&self) -> Box<dyn Light> {
+        match *self {
+            LightKind::Point(light) => Box::new(light),
+        }
+    }
+}
+
+pub(crate) mod saved_lights {
+    use super::{Light, LightKind};
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        lights: &[Box<dyn Light>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut saved = Vec::with_capacity(lights.len());
+        for light in lights {
+            let kind = light
+                .kind()
+                .ok_or_else(|| S::Error::custom(format!("can't save {:?}", light)))?;
+            saved.push(kind);
+        }
+
+        saved.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Box<dyn Light>>, D::Error> {
+        let saved: Vec<LightKind> = Vec::deserialize(deserializer)?;
+
+        Ok(saved.iter().map(LightKind::build).collect())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct PointLight {
     pub(crate) position: Tuple,
     pub(crate) intensity: Color,
+    // radius of the disk the shadow rays are spread over, 0 gives hard shadows
+    pub(crate) shadow_softness: f64,
 }
 
 impl PointLight {
@@ -12,6 +103,43 @@ impl PointLight {
         PointLight {
             position,
             intensity,
+            shadow_softness: 0.,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn position(&self) -> Tuple {
+        self.position
+    }
+
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn clone_box(&self) -> Box<dyn Light> {
+        Box::new(*self)
+    }
+
+    fn kind(&self) -> Option<LightKind> {
+        Some(LightKind::Point(*self))
+    }
+
+    // soft lights spread the samples over a disk facing the point. The spiral is turned by an
+    // angle seeded with the point, which trades banding at the shadow edges for noise while
+    // renders stay reproducible.
+    fn samples(&self, point: Tuple, sample: &mut dyn FnMut(Tuple)) {
+        if self.shadow_softness <= 0. {
+            return sample(self.position);
+        }
+
+        let seed = [point.x, point.y, point.z]
+            .iter()
+            .fold(0, |hash, value| splitmix64(hash ^ value.to_bits()));
+        let rotation = Sequence::new(seed).next_f64() * 2. * PI;
+        let to_light = (self.position - point).normalize();
+        for offset in disk_spiral(to_light, SHADOW_SAMPLES, rotation) {
+            sample(self.position + offset * self.shadow_softness);
         }
     }
 }
@@ -25,6 +153,7 @@ impl PartialEq for PointLight {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::f64_eq;
     use crate::math::tuple::Tuple;
     use crate::scene::shading::color::Color;
 
@@ -36,5 +165,51 @@ mod tests {
 
         assert_eq!(position, light.position);
         assert_eq!(intensity, light.intensity);
+        assert_eq!(0., light.shadow_softness);
+    }
+
+    #[test]
+    fn test_point_light_intensity_at_follows_shadow() {
+        let mut light = PointLight::new(Tuple::point(0., 10., 0.), Color::white());
+        let point = Tuple::point(0., 0., 0.);
+
+        assert_eq!(1., light.intensity_at(point, &|_| false));
+        assert_eq!(0., light.intensity_at(point, &|_| true));
+
+        // only the samples on the positive x side are blocked
+        light.shadow_softness = 1.;
+        let intensity = light.intensity_at(point, &|target| target.x > 0.);
+        assert!(intensity > 0. && intensity < 1.);
+    }
+
+    #[test]
+    fn test_soft_point_light_samples_a_disk() {
+        let mut light = PointLight::new(Tuple::point(0., 10., 0.), Color::white());
+        let samples = |light: &PointLight| {
+            let mut samples = vec![];
+            light.samples(Tuple::point(0., 0., 0.), &mut |target| samples.push(target));
+            samples
+        };
+        assert_eq!(vec![light.position], samples(&light));
+
+        light.shadow_softness = 1.;
+        let samples = samples(&light);
+
+        assert_eq!(SHADOW_SAMPLES, samples.len());
+        for sample in samples {
+            assert!(f64_eq(10., sample.y));
+            assert!((sample - light.position).magnitude3() <= 1.);
+        }
+    }
+
+    #[test]
+    fn test_saved_lights_are_rebuilt() {
+        let mut light = PointLight::new(Tuple::point(1., 2., 3.), Color::new(0.5, 0.5, 0.5));
+        light.shadow_softness = 0.25;
+        let saved = serde_json::to_string(&light.kind()).unwrap();
+        let loaded: LightKind = serde_json::from_str(&saved).unwrap();
+
+        assert_eq!(Some(loaded), light.kind());
+        assert_eq!(light.position, loaded.build().position());
     }
 }
@@ -3,12 +3,29 @@ use crate::math::tuple::Tuple;
 use crate::pattern::Pattern;
 use crate::primitives::shape::Shape;
 use crate::scene::shading::color::Color;
-use crate::scene::shading::light::PointLight;
+use crate::scene::shading::light::Light;
+use serde::ser::Error;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct Material {
     color: Color,
+    // patterns are trait objects and can't be saved, saving a material with one fails instead
+    // of silently losing the pattern
+    #[serde(
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "unsaved_pattern"
+    )]
     pattern: Option<Box<dyn Pattern>>,
+    // tangent space normals encoded as colors, each channel mapped from [0, 1] to [-1, 1], so
+    // (0.5, 0.5, 1) keeps the geometric normal. Can't be saved either.
+    #[serde(
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "unsaved_pattern"
+    )]
+    normal_map: Option<Box<dyn Pattern>>,
     ambient: f64,
     diffuse: f64,
     specular: f64,
@@ -16,13 +33,54 @@ pub(crate) struct Material {
     reflective: f64,
     transparency: f64,
     refractive_index: f64,
+    // dielectrics reflect more at grazing angles, with this the reflection is weighted by
+    // Schlick's approximation even if the material isn't transparent
+    #[serde(default)]
+    fresnel: bool,
+    emission: Color,
+    // thin film coating in nanometers, 0 disables the interference colors
+    film_thickness: f64,
+    film_refractive_index: f64,
 }
 
+fn unsaved_pattern<S: Serializer>(
+    pattern: &Option<Box<dyn Pattern>>,
+    _: S,
+) -> Result<S::Ok, S::Error> {
+    Err(S::Error::custom(format!("can't save {:?}", pattern)))
+}
+
+// common refractive indices, so scenes don't need to remember the numbers
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum RefractiveIndex {
+    Vacuum,
+    Air,
+    Water,
+    Glass,
+    Diamond,
+}
+
+impl RefractiveIndex {
+    pub(crate) fn value(self) -> f64 {
+        match self {
+            RefractiveIndex::Vacuum => 1.,
+            RefractiveIndex::Air => 1.00029,
+            RefractiveIndex::Water => 1.33,
+            RefractiveIndex::Glass => 1.5,
+            RefractiveIndex::Diamond => 2.417,
+        }
+    }
+}
+
+// wavelengths in nanometers the red, green and blue channels are evaluated at
+const WAVELENGTHS: (f64, f64, f64) = (650., 510., 475.);
+
 impl Material {
     pub(crate) fn new() -> Material {
         Material {
             color: Color::new(1., 1., 1.),
             pattern: None,
+            normal_map: None,
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
@@ -30,31 +88,93 @@ impl Material {
             reflective: 0.0,
             transparency: 0.0,
             refractive_index: 1.0,
+            fresnel: false,
+            emission: Color::black(),
+            film_thickness: 0.,
+            film_refractive_index: 1.33,
         }
     }
 
     pub(crate) fn lighting(
         &self,
         object: &dyn Shape,
-        light: PointLight,
+        light: &dyn Light,
         position: Tuple,
         eye_v: Tuple,
         normal_v: Tuple,
         in_shadow: bool,
     ) -> Color {
-        let color = match &self.pattern {
+        let visibility = if in_shadow { 0. } else { 1. };
+
+        self.lighting_multi(object, &[(light, visibility)], position, eye_v, normal_v)
+    }
+
+    // shades a point lit by several lights, each paired with its visibility from 0 (in shadow)
+    // to 1 (fully lit). The surface color and the ambient term don't depend on the light, so
+    // they are computed only once.
+    pub(crate) fn lighting_multi(
+        &self,
+        object: &dyn Shape,
+        lights: &[(&dyn Light, f64)],
+        position: Tuple,
+        eye_v: Tuple,
+        normal_v: Tuple,
+    ) -> Color {
+        let color = self.surface_color(object, position);
+
+        let mut intensity = Color::black();
+        let mut direct = Color::black();
+        for (light, visibility) in lights {
+            intensity = intensity + light.intensity();
+
+            if *visibility > 0. {
+                direct = direct
+                    + self.direct_lighting(color, *light, position, eye_v, normal_v) * *visibility;
+            }
+        }
+
+        // compute the ambient contribution
+        let ambient = color * intensity * self.ambient;
+
+        ambient + direct + self.emission
+    }
+
+    // used when there is no light at all, the surface is only lit by the ambient term
+    pub(crate) fn ambient_lighting(&self, object: &dyn Shape, position: Tuple) -> Color {
+        self.ambient_color(object, position, Color::white()) + self.emission
+    }
+
+    // the ambient term alone, for the summed intensity of all lights
+    pub(crate) fn ambient_color(
+        &self,
+        object: &dyn Shape,
+        position: Tuple,
+        intensity: Color,
+    ) -> Color {
+        self.surface_color(object, position) * intensity * self.ambient
+    }
+
+    pub(crate) fn surface_color(&self, object: &dyn Shape, position: Tuple) -> Color {
+        match &self.pattern {
             Some(pattern) => pattern.pattern_at_shape(object, position),
             None => self.color,
-        };
+        }
+    }
 
+    // diffuse and specular contribution of a single light, without ambient and emission
+    pub(crate) fn direct_lighting(
+        &self,
+        color: Color,
+        light: &dyn Light,
+        position: Tuple,
+        eye_v: Tuple,
+        normal_v: Tuple,
+    ) -> Color {
         // combine the surface color with the light's color/intensity
-        let effective_color = color * light.intensity;
+        let effective_color = color * light.intensity();
 
         // find the direction to the light source
-        let light_v = (light.position - position).normalize();
-
-        // compute the ambient contribution
-        let ambient = effective_color * self.ambient;
+        let light_v = (light.position() - position).normalize();
 
         let mut diffuse = Color::new(0., 0., 0.);
         let mut specular = Color::new(0., 0., 0.);
@@ -63,7 +183,7 @@ impl Material {
         // light vector and the normal vector. A negative number means the
         // light is on the other side of the surface.
         let light_dot_normal = light_v.dot(normal_v);
-        if !in_shadow && light_dot_normal >= 0. {
+        if light_dot_normal >= 0. {
             // compute the diffuse contribution
             diffuse = effective_color * self.diffuse * light_dot_normal;
 
@@ -75,11 +195,54 @@ impl Material {
 
             if reflect_dot_eye > 0. {
                 let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.intensity * self.specular * factor;
+                specular =
+                    light.intensity() * self.thin_film(eye_v, normal_v) * self.specular * factor;
             }
         }
 
-        ambient + diffuse + specular
+        diffuse + specular
+    }
+
+    // the diffuse term of a single light alone, for previews without highlights
+    pub(crate) fn diffuse_lighting(
+        &self,
+        color: Color,
+        light: &dyn Light,
+        position: Tuple,
+        normal_v: Tuple,
+    ) -> Color {
+        let light_v = (light.position() - position).normalize();
+        let light_dot_normal = light_v.dot(normal_v);
+        if light_dot_normal < 0. {
+            return Color::black();
+        }
+
+        color * light.intensity() * self.diffuse * light_dot_normal
+    }
+
+    // tint of the light reflected by a thin film, like a soap bubble or oil on water. Light
+    // reflected at the top and the bottom of the film interferes depending on the path
+    // difference, which changes with the view angle. White without a film.
+    pub(crate) fn thin_film(&self, eye_v: Tuple, normal_v: Tuple) -> Color {
+        if self.film_thickness <= 0. {
+            return Color::white();
+        }
+
+        let cos_i = eye_v.dot(normal_v).abs().min(1.);
+        let sin2_t = (1. - cos_i.powi(2)) / self.film_refractive_index.powi(2);
+        let cos_t = (1. - sin2_t).sqrt();
+        let path_difference = 2. * self.film_refractive_index * self.film_thickness * cos_t;
+
+        // the reflection at the top of the film shifts the phase by half a wavelength
+        let intensity = |wavelength: f64| {
+            0.5 - 0.5 * (2. * std::f64::consts::PI * path_difference / wavelength).cos()
+        };
+
+        Color::new(
+            intensity(WAVELENGTHS.0),
+            intensity(WAVELENGTHS.1),
+            intensity(WAVELENGTHS.2),
+        )
     }
 
     pub(crate) fn get_color(&self) -> Color {
@@ -138,6 +301,18 @@ impl Material {
         self.refractive_index = new
     }
 
+    pub(crate) fn set_refractive_index_preset(&mut self, preset: RefractiveIndex) {
+        self.set_refractive_index(preset.value())
+    }
+
+    pub(crate) fn get_fresnel(&self) -> bool {
+        self.fresnel
+    }
+
+    pub(crate) fn set_fresnel(&mut self, new: bool) {
+        self.fresnel = new
+    }
+
     pub(crate) fn get_reflective(&self) -> f64 {
         self.reflective
     }
@@ -146,9 +321,135 @@ impl Material {
         self.reflective = new
     }
 
+    pub(crate) fn get_emission(&self) -> Color {
+        self.emission
+    }
+
+    pub(crate) fn set_emission(&mut self, new: Color) {
+        self.emission = new
+    }
+
     pub(crate) fn set_pattern(&mut self, new: Box<dyn Pattern>) {
         self.pattern = Some(new)
     }
+
+    pub(crate) fn set_normal_map(&mut self, new: Box<dyn Pattern>) {
+        self.normal_map = Some(new)
+    }
+
+    // tilts the normal by the normal map, in a tangent frame built around the normal itself
+    pub(crate) fn perturb_normal(
+        &self,
+        object: &dyn Shape,
+        position: Tuple,
+        normal_v: Tuple,
+    ) -> Tuple {
+        let normal_map = match &self.normal_map {
+            Some(normal_map) => normal_map,
+            None => return normal_v,
+        };
+        let encoded = normal_map.pattern_at_shape(object, position);
+
+        let helper = if normal_v.x.abs() < 0.9 {
+            Tuple::direction(1., 0., 0.)
+        } else {
+            Tuple::direction(0., 1., 0.)
+        };
+        let tangent = normal_v.cross(helper).normalize();
+        let bitangent = normal_v.cross(tangent);
+
+        (tangent * (encoded.red * 2. - 1.)
+            + bitangent * (encoded.green * 2. - 1.)
+            + normal_v * (encoded.blue * 2. - 1.))
+            .normalize()
+    }
+
+    pub(crate) fn set_thin_film(&mut self, thickness: f64, refractive_index: f64) {
+        self.film_thickness = thickness;
+        self.film_refractive_index = refractive_index;
+    }
+}
+
+// fluent alternative to the setters, starting from the default material
+pub(crate) struct MaterialBuilder {
+    material: Material,
+}
+
+impl MaterialBuilder {
+    pub(crate) fn new() -> MaterialBuilder {
+        MaterialBuilder {
+            material: Material::new(),
+        }
+    }
+
+    pub(crate) fn color(mut self, color: Color) -> MaterialBuilder {
+        self.material.color = color;
+        self
+    }
+
+    pub(crate) fn ambient(mut self, ambient: f64) -> MaterialBuilder {
+        self.material.ambient = ambient;
+        self
+    }
+
+    pub(crate) fn diffuse(mut self, diffuse: f64) -> MaterialBuilder {
+        self.material.diffuse = diffuse;
+        self
+    }
+
+    pub(crate) fn specular(mut self, specular: f64) -> MaterialBuilder {
+        self.material.specular = specular;
+        self
+    }
+
+    pub(crate) fn shininess(mut self, shininess: f64) -> MaterialBuilder {
+        self.material.shininess = shininess;
+        self
+    }
+
+    pub(crate) fn reflective(mut self, reflective: f64) -> MaterialBuilder {
+        self.material.reflective = reflective;
+        self
+    }
+
+    pub(crate) fn transparency(mut self, transparency: f64) -> MaterialBuilder {
+        self.material.transparency = transparency;
+        self
+    }
+
+    pub(crate) fn refractive_index(mut self, refractive_index: f64) -> MaterialBuilder {
+        self.material.refractive_index = refractive_index;
+        self
+    }
+
+    pub(crate) fn fresnel(mut self, fresnel: bool) -> MaterialBuilder {
+        self.material.fresnel = fresnel;
+        self
+    }
+
+    pub(crate) fn pattern(mut self, pattern: Box<dyn Pattern>) -> MaterialBuilder {
+        self.material.pattern = Some(pattern);
+        self
+    }
+
+    pub(crate) fn normal_map(mut self, normal_map: Box<dyn Pattern>) -> MaterialBuilder {
+        self.material.normal_map = Some(normal_map);
+        self
+    }
+
+    pub(crate) fn emission(mut self, emission: Color) -> MaterialBuilder {
+        self.material.emission = emission;
+        self
+    }
+
+    pub(crate) fn thin_film(mut self, thickness: f64, refractive_index: f64) -> MaterialBuilder {
+        self.material.set_thin_film(thickness, refractive_index);
+        self
+    }
+
+    pub(crate) fn build(self) -> Material {
+        self.material
+    }
 }
 
 impl PartialEq for Material {
@@ -158,6 +459,9 @@ impl PartialEq for Material {
             && f64_eq(self.diffuse, other.diffuse)
             && f64_eq(self.specular, other.specular)
             && f64_eq(self.shininess, other.shininess)
+            && f64_eq(self.reflective, other.reflective)
+            && f64_eq(self.transparency, other.transparency)
+            && f64_eq(self.refractive_index, other.refractive_index)
     }
 }
 
@@ -166,9 +470,39 @@ mod tests {
     use super::*;
     use crate::math::tuple::Tuple;
     use crate::pattern::stripe::StripePattern;
+    use crate::pattern::PatternProps;
     use crate::primitives::sphere::Sphere;
     use crate::scene::shading::color::Color;
     use crate::scene::shading::light::PointLight;
+    use std::f64::consts::FRAC_1_SQRT_2;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug)]
+    struct CountingPattern {
+        props: PatternProps,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Pattern for CountingPattern {
+        fn clone_box(&self) -> Box<dyn Pattern> {
+            Box::new(self.clone())
+        }
+
+        fn pattern_at(&self, point: Tuple) -> Color {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            Color::new(point.x.abs(), 0.5, 0.25)
+        }
+
+        fn get_props(&self) -> &PatternProps {
+            &self.props
+        }
+
+        fn mut_props(&mut self) -> &mut PatternProps {
+            &mut self.props
+        }
+    }
 
     #[test]
     fn test_default_material() {
@@ -192,7 +526,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(1.9, 1.9, 1.9), r);
     }
 
@@ -207,7 +541,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(1.0, 1.0, 1.0), r);
     }
 
@@ -222,7 +556,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 10., -10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(0.7364, 0.7364, 0.7364), r);
     }
 
@@ -237,7 +571,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 10., -10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(1.636396, 1.636396, 1.636396), r);
     }
 
@@ -252,7 +586,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 0., 10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(0.1, 0.1, 0.1), r);
     }
 
@@ -267,7 +601,7 @@ mod tests {
         let normal_v = Tuple::direction(0., 0., -1.);
         let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
 
-        let r = m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        let r = m.lighting(&object, &light, position, eye_v, normal_v, in_shadow);
         assert_eq!(Color::new(0.1, 0.1, 0.1), r);
     }
 
@@ -289,7 +623,7 @@ mod tests {
 
         let c1 = m.lighting(
             &object,
-            light,
+            &light,
             Tuple::point(0.9, 0., 0.),
             eye_v,
             normal_v,
@@ -297,7 +631,7 @@ mod tests {
         );
         let c2 = m.lighting(
             &object,
-            light,
+            &light,
             Tuple::point(1.1, 0., 0.),
             eye_v,
             normal_v,
@@ -322,4 +656,237 @@ mod tests {
         assert_eq!(0.0, m.transparency);
         assert_eq!(1.0, m.refractive_index);
     }
+
+    #[test]
+    fn test_emission_is_added_even_when_in_shadow() {
+        let mut m = Material::new();
+        m.set_emission(Color::new(0.5, 0.25, 0.));
+        let object = Sphere::new();
+
+        let eye_v = Tuple::direction(0., 0., -1.);
+        let normal_v = Tuple::direction(0., 0., -1.);
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
+
+        let r = m.lighting(
+            &object,
+            &light,
+            Tuple::point(0., 0., 0.),
+            eye_v,
+            normal_v,
+            true,
+        );
+        assert_eq!(Color::new(0.6, 0.35, 0.1), r);
+    }
+
+    #[test]
+    fn test_fully_emissive_material_glows_without_any_light() {
+        let m = MaterialBuilder::new()
+            .ambient(0.)
+            .diffuse(0.)
+            .specular(0.)
+            .emission(Color::new(0.2, 0.8, 1.))
+            .build();
+        let object = Sphere::new();
+
+        let eye_v = Tuple::direction(0., 0., -1.);
+        let normal_v = Tuple::direction(0., 0., -1.);
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
+
+        let r = m.lighting(
+            &object,
+            &light,
+            Tuple::point(0., 0., 0.),
+            eye_v,
+            normal_v,
+            true,
+        );
+        assert_eq!(Color::new(0.2, 0.8, 1.), r);
+        assert_eq!(Color::black(), Material::new().get_emission());
+    }
+
+    #[test]
+    fn test_lighting_multi_equals_sum_of_single_lights_and_samples_pattern_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut m = Material::new();
+        m.set_pattern(Box::new(CountingPattern {
+            props: PatternProps::default(),
+            calls: calls.clone(),
+        }));
+        let object = Sphere::new();
+
+        let position = Tuple::point(0.5, 0., 0.);
+        let eye_v = Tuple::direction(0., 0., -1.);
+        let normal_v = Tuple::direction(0., 0., -1.);
+        let lights = [
+            (
+                PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.)),
+                1.,
+            ),
+            (
+                PointLight::new(Tuple::point(10., 10., -10.), Color::new(0.5, 0.2, 0.1)),
+                1.,
+            ),
+            (
+                PointLight::new(Tuple::point(-10., 0., -10.), Color::new(0.3, 0.3, 0.3)),
+                0.,
+            ),
+        ];
+
+        let mut naive = Color::black();
+        for (light, visibility) in &lights {
+            let in_shadow = *visibility == 0.;
+            naive = naive + m.lighting(&object, light, position, eye_v, normal_v, in_shadow);
+        }
+        calls.store(0, Ordering::SeqCst);
+
+        let lights: Vec<(&dyn Light, f64)> = lights
+            .iter()
+            .map(|(light, visibility)| (light as &dyn Light, *visibility))
+            .collect();
+        let multi = m.lighting_multi(&object, &lights, position, eye_v, normal_v);
+
+        assert_eq!(naive, multi);
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_cloned_material_keeps_its_pattern() {
+        let mut m = Material::new();
+        m.set_pattern(Box::new(StripePattern::new(Color::white(), Color::black())));
+        let object = Sphere::new();
+
+        let clone = m.clone();
+
+        for x in [0., 0.9, 1., 1.5, -0.1] {
+            let position = Tuple::point(x, 0., 0.);
+            assert_eq!(
+                m.surface_color(&object, position),
+                clone.surface_color(&object, position)
+            );
+        }
+        assert_eq!(
+            Color::black(),
+            clone.surface_color(&object, Tuple::point(1., 0., 0.))
+        );
+    }
+
+    #[test]
+    fn test_builder_equals_material_built_with_setters() {
+        let built = MaterialBuilder::new()
+            .color(Color::new(0.2, 0.4, 0.6))
+            .ambient(0.3)
+            .diffuse(0.5)
+            .specular(0.7)
+            .shininess(50.)
+            .reflective(0.8)
+            .transparency(0.9)
+            .refractive_index(1.33)
+            .pattern(Box::new(StripePattern::new(Color::white(), Color::black())))
+            .build();
+
+        let mut m = Material::new();
+        m.set_color(Color::new(0.2, 0.4, 0.6));
+        m.set_ambient(0.3);
+        m.set_diffuse(0.5);
+        m.set_specular(0.7);
+        m._set_shininess(50.);
+        m.set_reflective(0.8);
+        m.set_transparency(0.9);
+        m.set_refractive_index(1.33);
+
+        assert_eq!(m, built);
+        assert_eq!(m.get_reflective(), built.get_reflective());
+        assert_eq!(m.get_transparency(), built.get_transparency());
+        assert_eq!(m.get_refractive_index(), built.get_refractive_index());
+        assert!(built.pattern.is_some());
+    }
+
+    #[test]
+    fn test_flat_normal_map_keeps_normal() {
+        let object = Sphere::new();
+        let flat = Color::new(0.5, 0.5, 1.);
+        let m = MaterialBuilder::new()
+            .normal_map(Box::new(StripePattern::new(flat, flat)))
+            .build();
+        let normal_v = Tuple::direction(0., 1., 0.);
+
+        assert_eq!(
+            normal_v,
+            m.perturb_normal(&object, Tuple::point(0., 1., 0.), normal_v)
+        );
+        assert_eq!(
+            normal_v,
+            Material::new().perturb_normal(&object, Tuple::point(0., 1., 0.), normal_v)
+        );
+    }
+
+    #[test]
+    fn test_tilted_normal_map_perturbs_normal_towards_tangent() {
+        let object = Sphere::new();
+        let tilted = Color::new(1., 0.5, 1.);
+        let m = MaterialBuilder::new()
+            .normal_map(Box::new(StripePattern::new(tilted, tilted)))
+            .build();
+
+        // the tangent of an upwards normal is -z
+        let n = m.perturb_normal(
+            &object,
+            Tuple::point(0., 1., 0.),
+            Tuple::direction(0., 1., 0.),
+        );
+
+        assert_eq!(Tuple::direction(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2), n);
+    }
+
+    #[test]
+    fn test_refractive_index_presets() {
+        let mut m = Material::new();
+
+        m.set_refractive_index_preset(RefractiveIndex::Water);
+        assert_eq!(1.33, m.get_refractive_index());
+
+        m.set_refractive_index_preset(RefractiveIndex::Diamond);
+        assert!(f64_eq(2.417, m.get_refractive_index()));
+
+        m.set_refractive_index_preset(RefractiveIndex::Vacuum);
+        assert_eq!(1., m.get_refractive_index());
+
+        // air bends light barely more than vacuum
+        m.set_refractive_index_preset(RefractiveIndex::Air);
+        assert!(m.get_refractive_index() > 1. && m.get_refractive_index() < 1.001);
+    }
+
+    #[test]
+    fn test_thin_film_color_changes_with_view_angle() {
+        let mut m = Material::new();
+        m.set_thin_film(400., 1.33);
+        let normal_v = Tuple::direction(0., 0., -1.);
+
+        let head_on = m.thin_film(Tuple::direction(0., 0., -1.), normal_v);
+        let grazing = m.thin_film(
+            Tuple::direction(0., 2f64.sqrt() / 2., -2f64.sqrt() / 2.),
+            normal_v,
+        );
+
+        assert_ne!(head_on, grazing);
+        assert_ne!(head_on.red, head_on.blue);
+    }
+
+    #[test]
+    fn test_thin_film_without_thickness_keeps_base_color() {
+        let s = Sphere::new();
+        let m = Material::new();
+        let mut filmless = Material::new();
+        filmless.set_thin_film(0., 1.5);
+        let position = Tuple::point(0., 0., 0.);
+        let eye_v = Tuple::direction(0., 0., -1.);
+        let normal_v = Tuple::direction(0., 0., -1.);
+        let light = PointLight::new(Tuple::point(0., 0., -10.), Color::new(1., 1., 1.));
+
+        assert_eq!(Color::white(), filmless.thin_film(eye_v, normal_v));
+        assert_eq!(
+            m.lighting(&s, &light, position, eye_v, normal_v, false),
+            filmless.lighting(&s, &light, position, eye_v, normal_v, false)
+        );
+    }
 }
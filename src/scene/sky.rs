@@ -0,0 +1,85 @@
+use crate::math::tuple::Tuple;
+use crate::scene::shading::color::Color;
+use serde::{Deserialize, Serialize};
+
+// simple analytic sky for rays that miss every object, blending from the horizon to the
+// zenith with an optional sun disk
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SkyBackground {
+    zenith: Color,
+    horizon: Color,
+    sun: Option<Sun>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+struct Sun {
+    direction: Tuple,
+    color: Color,
+    // cosine of the angular radius, directions closer to the sun than this hit the disk
+    cos_radius: f64,
+}
+
+impl SkyBackground {
+    pub(crate) fn new(zenith: Color, horizon: Color) -> SkyBackground {
+        SkyBackground {
+            zenith,
+            horizon,
+            sun: None,
+        }
+    }
+
+    pub(crate) fn set_sun(&mut self, direction: Tuple, color: Color, angular_radius: f64) {
+        self.sun = Some(Sun {
+            direction: direction.normalize(),
+            color,
+            cos_radius: angular_radius.cos(),
+        });
+    }
+
+    // everything below the horizon gets the horizon color
+    pub(crate) fn color_at(&self, direction: Tuple) -> Color {
+        let direction = direction.normalize();
+
+        if let Some(sun) = self.sun {
+            if direction.dot(sun.direction) >= sun.cos_radius {
+                return sun.color;
+            }
+        }
+
+        self.horizon.lerp(self.zenith, direction.y.max(0.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sky() -> SkyBackground {
+        let mut sky = SkyBackground::new(Color::new(0.1, 0.3, 0.8), Color::new(0.8, 0.9, 1.));
+        sky.set_sun(Tuple::direction(1., 1., 0.), Color::new(10., 9., 8.), 0.05);
+
+        sky
+    }
+
+    #[test]
+    fn test_sky_color_towards_sun_zenith_and_horizon() {
+        let sky = sky();
+
+        assert_eq!(
+            Color::new(10., 9., 8.),
+            sky.color_at(Tuple::direction(2., 2., 0.))
+        );
+        assert_eq!(
+            Color::new(0.1, 0.3, 0.8),
+            sky.color_at(Tuple::direction(0., 1., 0.))
+        );
+        assert_eq!(
+            Color::new(0.8, 0.9, 1.),
+            sky.color_at(Tuple::direction(0., 0., 1.))
+        );
+        assert_eq!(
+            Color::new(0.8, 0.9, 1.),
+            sky.color_at(Tuple::direction(0., -1., 0.))
+        );
+    }
+}
@@ -1,27 +1,32 @@
 use crate::math::tuple::Tuple;
-use crate::math::EPSILON;
+use crate::math::{f64_eq, EPSILON};
 use crate::primitives::shape::Shape;
+use crate::scene::shading::color::Color;
 use crate::scene::tracing::ray::Ray;
 use std::cmp::Ordering;
+use std::ops::Index;
+use std::slice::Iter;
 use uuid::Uuid;
 
 #[derive(Debug)]
 pub(crate) struct Intersection<'a> {
-    pub(crate) id: Uuid,
     pub(crate) t: f64,
     pub(crate) object: &'a dyn Shape,
 }
 
 impl<'a> Intersection<'a> {
-    pub(crate) fn new(t: f64, object: &dyn Shape) -> Intersection {
-        Intersection {
-            id: Uuid::new_v4(),
-            t,
-            object,
-        }
+    pub(crate) fn new(t: f64, object: &dyn Shape) -> Intersection<'_> {
+        Intersection { t, object }
     }
 
-    pub(crate) fn prepare_computation(&self, ray: Ray, xs: &Intersections) -> Computation {
+    // an intersection enters the object when the ray points against the surface normal
+    pub(crate) fn is_entering(&self, ray: Ray) -> bool {
+        let normal_v = self.object.normal_at(ray.position(self.t));
+
+        normal_v.dot(ray.direction) < 0.
+    }
+
+    pub(crate) fn prepare_computation(&self, ray: Ray, xs: &Intersections<'_>) -> Computation<'_> {
         let point = ray.position(self.t);
         let eye_v = -ray.direction;
 
@@ -31,9 +36,15 @@ impl<'a> Intersection<'a> {
             inside = true;
             normal_v = -normal_v;
         }
-        let reflect_v = ray.direction.reflect(normal_v);
+        // the offset points stay on the geometric normal, only shading uses the mapped one
         let over_point = point + normal_v * EPSILON;
         let under_point = point - normal_v * EPSILON;
+        let normal_v =
+            self.object
+                .get_props()
+                .get_material()
+                .perturb_normal(self.object, point, normal_v);
+        let reflect_v = ray.direction.reflect(normal_v);
 
         let mut containers: Vec<&dyn Shape> = vec![];
 
@@ -90,9 +101,9 @@ impl<'a> Intersection<'a> {
             }
         }
 
-        Computation::new(
-            self.t,
-            self.object,
+        Computation {
+            t: self.t,
+            object: self.object,
             point,
             over_point,
             under_point,
@@ -102,13 +113,15 @@ impl<'a> Intersection<'a> {
             inside,
             n1,
             n2,
-        )
+        }
     }
 }
 
+// intersections are identified by the object they hit and their distance, so an intersection
+// created independently for the same object still matches the one in the list
 impl<'a> PartialEq for Intersection<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        self.object.get_id() == other.object.get_id() && f64_eq(self.t, other.t)
     }
 }
 
@@ -128,34 +141,6 @@ pub(crate) struct Computation<'a> {
 }
 
 impl<'a> Computation<'a> {
-    fn new(
-        t: f64,
-        object: &'a dyn Shape,
-        point: Tuple,
-        over_point: Tuple,
-        under_point: Tuple,
-        eye_v: Tuple,
-        normal_v: Tuple,
-        reflect_v: Tuple,
-        inside: bool,
-        n1: f64,
-        n2: f64,
-    ) -> Computation {
-        Computation {
-            t,
-            object,
-            point,
-            over_point,
-            under_point,
-            eye_v,
-            normal_v,
-            reflect_v,
-            inside,
-            n1,
-            n2,
-        }
-    }
-
     pub(crate) fn schlick(&self) -> f64 {
         // find the cosine of the angle between the eye and normal vectors
         let mut cos = self.eye_v.dot(self.normal_v);
@@ -178,6 +163,32 @@ impl<'a> Computation<'a> {
 
         r0 + (1. - r0) * (1. - cos).powi(5)
     }
+
+    // what isn't reflected is refracted: adds that share of the refracted color to the
+    // surface and returns the reflectance the reflected ray is weighted with
+    pub(crate) fn fresnel_blend(&self, surface: Color, refracted: Color) -> (Color, f64) {
+        let reflectance = self.schlick();
+
+        (surface + refracted * (1. - reflectance), reflectance)
+    }
+
+    pub(crate) fn to_owned_hit(&self) -> OwnedHit {
+        OwnedHit {
+            t: self.t,
+            object_id: self.object.get_id(),
+            point: self.point,
+            normal: self.normal_v,
+        }
+    }
+}
+
+// hit data without the borrowed shape, so it can be sent to other threads or cached
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct OwnedHit {
+    pub(crate) t: f64,
+    pub(crate) object_id: Uuid,
+    pub(crate) point: Tuple,
+    pub(crate) normal: Tuple,
 }
 
 #[derive(Debug)]
@@ -190,15 +201,21 @@ impl<'a> Intersections<'a> {
         Intersections { items: vec![] }
     }
 
+    pub(crate) fn with_capacity(capacity: usize) -> Intersections<'a> {
+        Intersections {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
     pub(crate) fn push(&mut self, intersection: Intersection<'a>) {
         self.items.push(intersection)
     }
 
-    pub(crate) fn first(&self) -> Option<&Intersection> {
+    pub(crate) fn first(&self) -> Option<&Intersection<'_>> {
         self.items.first()
     }
 
-    pub(crate) fn last(&self) -> Option<&Intersection> {
+    pub(crate) fn last(&self) -> Option<&Intersection<'_>> {
         self.items.last()
     }
 
@@ -206,17 +223,40 @@ impl<'a> Intersections<'a> {
         self.items.len()
     }
 
-    pub(crate) fn get(&self, index: usize) -> Option<&Intersection> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    // keeps the allocation so the buffer can be reused for the next ray
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&Intersection<'_>> {
         match self.items.get(index) {
             Some(intersection) => Some(intersection),
             None => None,
         }
     }
 
+    pub(crate) fn iter(&self) -> Iter<'_, Intersection<'a>> {
+        self.items.iter()
+    }
+
     pub(crate) fn merge(&mut self, other: Intersections<'a>) {
-        for item in other.items {
-            self.items.push(item);
-        }
+        self.items.extend(other.items);
+    }
+
+    pub(crate) fn retain(&mut self, keep: impl FnMut(&Intersection<'a>) -> bool) {
+        self.items.retain(keep);
+    }
+
+    pub(crate) fn is_sorted(&self) -> bool {
+        self.items.windows(2).all(|pair| pair[0].t <= pair[1].t)
     }
 
     pub(crate) fn sort(&mut self) {
@@ -231,10 +271,29 @@ impl<'a> Intersections<'a> {
         })
     }
 
-    pub(crate) fn hit(&self) -> Option<&Intersection> {
-        let mut result: Option<&Intersection> = None;
+    // sorts by t like `sort`, but intersections sharing the same t (touching objects) are
+    // ordered entering before exiting, so the refraction containers see the next object
+    // before the previous one is left
+    pub(crate) fn sort_along(&mut self, ray: Ray) {
+        self.sort();
+
+        let mut start = 0;
+        while start < self.items.len() {
+            let mut end = start + 1;
+            while end < self.items.len() && f64_eq(self.items[start].t, self.items[end].t) {
+                end += 1;
+            }
+            if end - start > 1 {
+                self.items[start..end].sort_by_key(|i| !i.is_entering(ray));
+            }
+            start = end;
+        }
+    }
 
-        for intersection in &self.items {
+    pub(crate) fn hit(&self) -> Option<&Intersection<'_>> {
+        let mut result: Option<&Intersection<'_>> = None;
+
+        for intersection in self {
             if intersection.t > 0. {
                 match result {
                     Some(i) => {
@@ -251,12 +310,29 @@ impl<'a> Intersections<'a> {
     }
 }
 
+impl<'a, 'b> IntoIterator for &'b Intersections<'a> {
+    type Item = &'b Intersection<'a>;
+    type IntoIter = Iter<'b, Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'a> Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::math::transformation::{scaling, translation};
     use crate::math::tuple::Tuple;
-    use crate::math::{f64_eq, EPSILON};
+    use crate::pattern::stripe::StripePattern;
     use crate::primitives::plane::Plane;
     use crate::primitives::sphere::Sphere;
     use crate::scene::tracing::ray::Ray;
@@ -285,6 +361,69 @@ mod tests {
         assert_eq!(2., xs.get(1).unwrap().t);
     }
 
+    #[test]
+    fn test_intersections_with_capacity() {
+        let s = Sphere::new();
+        let mut xs = Intersections::with_capacity(4);
+
+        assert!(xs.is_empty());
+        assert!(xs.capacity() >= 4);
+
+        xs.push(Intersection::new(1., &s));
+        xs.push(Intersection::new(2., &s));
+
+        assert_eq!(2, xs.len());
+        assert_eq!(2., xs.get(1).unwrap().t);
+    }
+
+    #[test]
+    fn test_clearing_intersections_retains_capacity() {
+        let s = Sphere::new();
+        let mut xs = Intersections::with_capacity(8);
+        xs.push(Intersection::new(1., &s));
+        xs.push(Intersection::new(2., &s));
+        let capacity = xs.capacity();
+
+        xs.clear();
+
+        assert!(xs.is_empty());
+        assert_eq!(capacity, xs.capacity());
+    }
+
+    #[test]
+    fn test_new_intersections_are_empty_until_pushed() {
+        let s = Sphere::new();
+        let mut xs = Intersections::new();
+
+        assert!(xs.is_empty());
+
+        xs.push(Intersection::new(1., &s));
+
+        assert!(!xs.is_empty());
+    }
+
+    #[test]
+    fn test_iterating_and_indexing_sorted_intersections() {
+        let s = Sphere::new();
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(5., &s));
+        xs.push(Intersection::new(-3., &s));
+        xs.push(Intersection::new(2., &s));
+        xs.sort();
+
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(vec![-3., 2., 5.], ts);
+
+        let mut ts = vec![];
+        for i in &xs {
+            ts.push(i.t);
+        }
+        assert_eq!(vec![-3., 2., 5.], ts);
+
+        assert_eq!(2., xs[1].t);
+        assert_eq!(s.get_id(), xs[2].object.get_id());
+    }
+
     #[test]
     fn test_hit_when_all_intersections_have_positive_t() {
         let s = Sphere::new();
@@ -362,6 +501,22 @@ mod tests {
         assert_eq!(computation.normal_v, Tuple::direction(0., 0., -1.));
     }
 
+    #[test]
+    fn test_owned_hit_from_computation() {
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let mut s = Sphere::new();
+        s.mut_props().set_transform(translation(0., 0., 1.));
+        let i = Intersection::new(5., &s);
+
+        let xs = Intersections::new();
+        let hit = i.prepare_computation(r, &xs).to_owned_hit();
+
+        assert_eq!(5., hit.t);
+        assert_eq!(s.get_id(), hit.object_id);
+        assert_eq!(Tuple::point(0., 0., 0.), hit.point);
+        assert_eq!(Tuple::direction(0., 0., -1.), hit.normal);
+    }
+
     #[test]
     fn test_hit_when_intersection_occurs_on_outside() {
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
@@ -474,25 +629,100 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_n1_and_n2_at_the_contact_point_of_touching_spheres() {
+        let mut a = Sphere::glass();
+        a.mut_props().set_transform(translation(0., 0., -1.));
+
+        let mut b = Sphere::glass();
+        b.mut_props().set_transform(translation(0., 0., 1.));
+        b.mut_props().set_material_refractive_index(2.);
+
+        let r = Ray::new(Tuple::point(0., 0., -4.), Tuple::direction(0., 0., 1.));
+
+        let mut xs = Intersections::new();
+        xs.merge(a.intersect(r).unwrap());
+        xs.merge(b.intersect(r).unwrap());
+        xs.sort_along(r);
+
+        assert!(f64_eq(4., xs.get(1).unwrap().t));
+        assert_eq!(b.get_id(), xs.get(1).unwrap().object.get_id());
+        assert_eq!(a.get_id(), xs.get(2).unwrap().object.get_id());
+
+        let comps = xs.get(1).unwrap().prepare_computation(r, &xs);
+        assert_eq!((1.5, 2.0), (comps.n1, comps.n2));
+
+        let comps = xs.get(2).unwrap().prepare_computation(r, &xs);
+        assert_eq!((2.0, 2.0), (comps.n1, comps.n2));
+    }
+
+    #[test]
+    fn test_n1_and_n2_for_independently_created_intersections() {
+        let shape = Sphere::glass();
+        let r = Ray::new(Tuple::point(0., 0., -4.), Tuple::direction(0., 0., 1.));
+        let xs = shape.intersect(r).unwrap();
+
+        let entering = Intersection::new(3., &shape);
+        let comps = entering.prepare_computation(r, &xs);
+        assert_eq!((1.0, 1.5), (comps.n1, comps.n2));
+
+        let exiting = Intersection::new(5., &shape);
+        let comps = exiting.prepare_computation(r, &xs);
+        assert_eq!((1.5, 1.0), (comps.n1, comps.n2));
+    }
+
     #[test]
     fn test_under_point_is_offset_below_the_surface() {
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
         let mut shape = Sphere::glass();
         shape.mut_props().set_transform(translation(0., 0., 1.));
 
-        let i = Intersection::new(5., &shape);
         let mut xs = Intersections::new();
-        xs.items.push(i);
+        xs.items.push(Intersection::new(5., &shape));
 
-        // because we dont care about n1 + n2, we can bypass the calculation by creating another
-        // intersection instance
-        let i = Intersection::new(5., &shape);
-        let comps = i.prepare_computation(r, &xs);
+        let comps = xs.first().unwrap().prepare_computation(r, &xs);
 
         assert!(comps.under_point.z > EPSILON / 2.);
         assert!(comps.point.z < comps.under_point.z);
     }
 
+    #[test]
+    fn test_normal_map_changes_shading_normal_but_not_offset_points() {
+        let r = Ray::new(Tuple::point(0., 1., 0.), Tuple::direction(0., -1., 0.));
+        let mut shape = Plane::new();
+        let tilted = Color::new(1., 0.5, 1.);
+        shape
+            .mut_props()
+            .set_normal_map(Box::new(StripePattern::new(tilted, tilted)));
+
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(1., &shape));
+        let comps = xs.first().unwrap().prepare_computation(r, &xs);
+
+        assert!(comps.normal_v.z < 0.);
+        assert_eq!(r.direction.reflect(comps.normal_v), comps.reflect_v);
+        assert_eq!(Tuple::point(0., EPSILON, 0.), comps.over_point);
+    }
+
+    #[test]
+    fn test_fresnel_blend_weights_reflection_and_refraction() {
+        let shape = Sphere::glass();
+        let r = Ray::new(
+            Tuple::point(0., 0., 2f64.sqrt() / 2.),
+            Tuple::direction(0., 1., 0.),
+        );
+        let mut xs = Intersections::new();
+        xs.push(Intersection::new(-2f64.sqrt() / 2., &shape));
+        xs.push(Intersection::new(2f64.sqrt() / 2., &shape));
+        let comps = xs.get(1).unwrap().prepare_computation(r, &xs);
+
+        // total internal reflection, nothing is refracted
+        let (color, reflectance) = comps.fresnel_blend(Color::black(), Color::new(1., 0., 0.));
+
+        assert_eq!(Color::black(), color);
+        assert_eq!(1., reflectance);
+    }
+
     #[test]
     fn test_schlick_approximation_under_total_internal_reflection() {
         let shape = Sphere::glass();
@@ -17,6 +17,11 @@ impl Ray {
     }
 
     pub(crate) fn transform(&self, m: M4) -> Ray {
+        self.transform_by(&m)
+    }
+
+    // same as transform, but borrows the matrix for the per ray hot path
+    pub(crate) fn transform_by(&self, m: &M4) -> Ray {
         Ray::new(m * self.origin, m * self.direction)
     }
 }
@@ -69,4 +74,15 @@ mod tests {
         assert_eq!(r2.origin, Tuple::point(2., 6., 12.));
         assert_eq!(r2.direction, Tuple::direction(0., 3., 0.));
     }
+
+    #[test]
+    fn test_transforming_a_ray_by_reference() {
+        let ray = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let m = scaling(2., 2., 2.).inverse().unwrap();
+
+        let r2 = ray.transform_by(&m);
+
+        assert_eq!(r2.origin, Tuple::point(0., 0., -2.5));
+        assert_eq!(r2.direction, Tuple::direction(0., 0., 0.5));
+    }
 }
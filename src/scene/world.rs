@@ -1,56 +1,387 @@
+use crate::math::transformation::scaling;
 use crate::primitives::shape::Shape;
+use crate::primitives::sphere::Sphere;
+use crate::scene::bvh::Bvh;
+use crate::scene::sampling::{disk_spiral, splitmix64};
 use crate::scene::shading::color::Color;
-use crate::scene::shading::light::PointLight;
+use crate::scene::shading::light::{Light, PointLight};
+use crate::scene::sky::SkyBackground;
 use crate::scene::tracing::intersection::{Computation, Intersections};
 use crate::scene::tracing::ray::Ray;
 
+use crate::math::matrix::M4;
 use crate::math::tuple::Tuple;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// only occluders closer than this darken the ambient term
+const AO_DISTANCE: f64 = 1.;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct RussianRoulette {
+    // secondary rays with at least this many bounces remaining are always traced
+    pub(crate) min_remaining: usize,
+    // different seeds give different, but reproducible, termination decisions
+    pub(crate) seed: u64,
+}
+
+impl RussianRoulette {
+    // pseudo random number in [0, 1), derived from the seed and the ray
+    fn random(&self, ray: Ray, remaining: usize) -> f64 {
+        let mut hash = self.seed ^ (remaining as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        for value in &[
+            ray.origin.x,
+            ray.origin.y,
+            ray.origin.z,
+            ray.direction.x,
+            ray.direction.y,
+            ray.direction.z,
+        ] {
+            hash = splitmix64(hash ^ value.to_bits());
+        }
 
+        (hash >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// scene mistakes found by World::validate, objects are referenced by their id
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Warning {
+    NoLights,
+    NonInvertibleTransform(Uuid),
+    NanTransform(Uuid),
+    // reflective plus transparency above 1 adds more light than arrives
+    ReflectiveAndTransparentAboveOne(Uuid),
+}
+
+#[derive(Copy, Clone, Debug)]
+enum RayKind {
+    Primary,
+    Shadow,
+    Reflection,
+    Refraction,
+}
+
+#[derive(Debug, Default)]
+struct RayCounters {
+    primary: AtomicUsize,
+    shadow: AtomicUsize,
+    reflection: AtomicUsize,
+    refraction: AtomicUsize,
+}
+
+// rays traced since the counters were enabled. Shadow rays include every occlusion test, so
+// ambient occlusion and emissive lighting add to them too.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct RenderStats {
+    pub(crate) primary_rays: usize,
+    pub(crate) shadow_rays: usize,
+    pub(crate) reflection_rays: usize,
+    pub(crate) refraction_rays: usize,
+}
+
+#[derive(Serialize, Deserialize)]
 pub(crate) struct World {
-    pub(crate) light: Option<PointLight>,
-    pub(crate) objects: Vec<Box<dyn Shape>>,
+    #[serde(with = "crate::scene::shading::light::saved_lights")]
+    pub(crate) lights: Vec<Box<dyn Light>>,
+    // only changed through the methods below, so the bvh never points at moved or removed
+    // objects
+    #[serde(with = "crate::primitives::shape::saved_shapes")]
+    objects: Vec<Box<dyn Shape>>,
+    // emissive objects additionally light their surroundings, sampled as point lights
+    pub(crate) emissive_lighting: bool,
+    // rebuilt on demand, a loaded world starts without one
+    #[serde(skip)]
+    bvh: Option<Bvh>,
+    pub(crate) roulette: Option<RussianRoulette>,
+    // color of rays that miss everything, black without a background
+    pub(crate) background: Option<SkyBackground>,
+    // rays cast over the hemisphere to approximate ambient occlusion, 0 turns it off
+    pub(crate) ao_samples: usize,
+    // opt-in profiling, shared with filtered copies of the world
+    #[serde(skip)]
+    counters: Option<Arc<RayCounters>>,
+}
+
+// copies keep the object ids and the bvh, so an id from one copy finds the same object in the other
+impl Clone for World {
+    fn clone(&self) -> World {
+        World {
+            lights: self.lights.clone(),
+            objects: self
+                .objects
+                .iter()
+                .map(|object| object.duplicate())
+                .collect(),
+            emissive_lighting: self.emissive_lighting,
+            bvh: self.bvh.clone(),
+            roulette: self.roulette,
+            background: self.background,
+            ao_samples: self.ao_samples,
+            counters: self.counters.clone(),
+        }
+    }
 }
 
 impl World {
     pub(crate) fn new() -> World {
         World {
-            light: None,
+            lights: vec![],
             objects: vec![],
+            emissive_lighting: false,
+            bvh: None,
+            roulette: None,
+            background: None,
+            ao_samples: 0,
+            counters: None,
         }
     }
 
+    // the book's default world: two concentric spheres lit from the upper left
+    pub(crate) fn demo() -> World {
+        let mut s1 = Sphere::new();
+        s1.mut_props().set_material_color(Color::new(0.8, 1., 0.6));
+        s1.mut_props().set_material_diffuse(0.7);
+        s1.mut_props().set_material_specular(0.2);
+
+        let mut s2 = Sphere::new();
+        s2.mut_props().set_transform(scaling(0.5, 0.5, 0.5));
+
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Tuple::point(-10., 10., -10.),
+            Color::new(1., 1., 1.),
+        ));
+        w.add_object(s1);
+        w.add_object(s2);
+
+        w
+    }
+
+    pub(crate) fn add_object<S: Shape + 'static>(&mut self, shape: S) {
+        self.add_boxed_object(Box::new(shape));
+    }
+
+    // objects added after building the bvh are tested directly, it stays valid
+    pub(crate) fn add_boxed_object(&mut self, shape: Box<dyn Shape>) {
+        self.objects.push(shape);
+    }
+
+    pub(crate) fn objects(&self) -> &[Box<dyn Shape>] {
+        &self.objects
+    }
+
+    // the object may be moved, so the bvh is dropped. Call build_bvh again after the changes.
+    pub(crate) fn object_mut(&mut self, index: usize) -> Option<&mut Box<dyn Shape>> {
+        self.bvh = None;
+
+        self.objects.get_mut(index)
+    }
+
+    // drops the bvh like object_mut, None if there is no object with the id
+    pub(crate) fn remove_object(&mut self, id: Uuid) -> Option<Box<dyn Shape>> {
+        let index = self.objects.iter().position(|o| o.get_id() == id)?;
+        self.bvh = None;
+
+        Some(self.objects.remove(index))
+    }
+
+    pub(crate) fn add_light<L: Light + 'static>(&mut self, light: L) {
+        self.lights.push(Box::new(light));
+    }
+
+    // moves a single object between frames, returns false if there is no object with the id
+    pub(crate) fn update_object_transform(&mut self, id: Uuid, transform: M4) -> bool {
+        let object = match self.objects.iter_mut().find(|o| o.get_id() == id) {
+            Some(object) => object,
+            None => return false,
+        };
+        object.mut_props().set_transform(transform);
+
+        if self.bvh.is_some() {
+            self.build_bvh();
+        }
+
+        true
+    }
+
+    // same as update_object_transform, but for a world shared with the renderer. Once a frame
+    // is rendered, the Arc is unique again and the world is updated in place. While a render
+    // still holds it, the world is copied first, so that render keeps its frame unchanged.
+    pub(crate) fn update_shared_object_transform(
+        world: &mut Arc<World>,
+        id: Uuid,
+        transform: M4,
+    ) -> bool {
+        // an unknown id shouldn't copy the world
+        if !world.objects.iter().any(|object| object.get_id() == id) {
+            return false;
+        }
+        Arc::make_mut(world).update_object_transform(id, transform)
+    }
+
+    // checks the scene before a long render
+    pub(crate) fn validate(&self) -> Vec<Warning> {
+        let mut warnings = vec![];
+
+        if self.lights.is_empty() {
+            warnings.push(Warning::NoLights);
+        }
+
+        for object in &self.objects {
+            let id = object.get_id();
+            let transform = object.get_props().get_transform();
+            let material = object.get_props().get_material();
+
+            let has_nan = (0..4).any(|row| (0..4).any(|col| transform.get(row, col).is_nan()));
+            if has_nan {
+                warnings.push(Warning::NanTransform(id));
+            } else if transform.inverse().is_none() {
+                warnings.push(Warning::NonInvertibleTransform(id));
+            }
+
+            if material.get_reflective() + material.get_transparency() > 1. {
+                warnings.push(Warning::ReflectiveAndTransparentAboveOne(id));
+            }
+        }
+
+        warnings
+    }
+
+    // speeds up intersect for scenes with many objects, call it after all objects are added
+    pub(crate) fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::build(&self.objects));
+    }
+
+    // starts counting traced rays from zero, counting costs an atomic add per ray
+    pub(crate) fn enable_stats(&mut self) {
+        self.counters = Some(Arc::new(RayCounters::default()));
+    }
+
+    // all zero while the counters aren't enabled
+    pub(crate) fn stats(&self) -> RenderStats {
+        match &self.counters {
+            Some(counters) => RenderStats {
+                primary_rays: counters.primary.load(Ordering::Relaxed),
+                shadow_rays: counters.shadow.load(Ordering::Relaxed),
+                reflection_rays: counters.reflection.load(Ordering::Relaxed),
+                refraction_rays: counters.refraction.load(Ordering::Relaxed),
+            },
+            None => RenderStats::default(),
+        }
+    }
+
+    fn count_ray(&self, kind: RayKind) {
+        if let Some(counters) = &self.counters {
+            let counter = match kind {
+                RayKind::Primary => &counters.primary,
+                RayKind::Shadow => &counters.shadow,
+                RayKind::Reflection => &counters.reflection,
+                RayKind::Refraction => &counters.refraction,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // reflections are followed in a loop instead of recursing, so long reflection chains
+    // are only bounded by remaining and not by the stack. Refraction still recurses.
     pub(crate) fn color_at(&self, ray: Ray, remaining: usize) -> Color {
-        let xs = self.intersect(ray);
-        match xs.hit() {
-            Some(hit) => {
-                let comp = hit.prepare_computation(ray, &xs);
+        self.color_at_visible(ray, remaining, None)
+    }
+
+    // like color_at, but the first hit is only searched among the visible objects, e.g. those
+    // inside a camera's view frustum. Reflections, refractions and shadows still see every
+    // object. Objects added after the mask was made count as visible.
+    pub(crate) fn color_at_visible(
+        &self,
+        ray: Ray,
+        remaining: usize,
+        visible: Option<&[bool]>,
+    ) -> Color {
+        self.count_ray(RayKind::Primary);
+
+        self.trace_ray(ray, remaining, 1., visible)
+    }
 
-                self.shade_hit(&comp, remaining)
+    // path_throughput is the weight of the path that led to this ray, only used to decide
+    // the roulette. The returned color is not scaled by it.
+    fn trace_ray(
+        &self,
+        ray: Ray,
+        remaining: usize,
+        path_throughput: f64,
+        visible: Option<&[bool]>,
+    ) -> Color {
+        let mut color = Color::black();
+        let mut throughput = 1.;
+        let mut ray = ray;
+        let mut remaining = remaining;
+        let mut visible = visible;
+
+        loop {
+            let xs = self.intersect_visible(ray, visible);
+            visible = None;
+            let comp = match xs.hit() {
+                Some(hit) => hit.prepare_computation(ray, &xs),
+                None => return color + self.background_color(ray) * throughput,
+            };
+
+            let (surface, reflection) =
+                self.shade_surface(&comp, remaining, path_throughput * throughput);
+            color = color + surface * throughput;
+
+            let (reflect_ray, weight) = match reflection {
+                Some(reflection) => reflection,
+                None => return color,
+            };
+            match self.roulette_weight(reflect_ray, remaining, path_throughput * throughput, weight)
+            {
+                Some(weight) => throughput *= weight,
+                None => return color,
             }
-            None => Color::new(0., 0., 0.),
+            self.count_ray(RayKind::Reflection);
+            ray = reflect_ray;
+            remaining -= 1;
+        }
+    }
+
+    fn background_color(&self, ray: Ray) -> Color {
+        match self.background {
+            Some(background) => background.color_at(ray.direction),
+            None => Color::black(),
         }
     }
 
     fn reflected_color(&self, computation: &Computation, remaining: usize) -> Color {
-        if remaining <= 0 {
-            return Color::new(0., 0., 0.);
+        match self.reflection(computation, remaining) {
+            Some((reflect_ray, weight)) => {
+                self.trace_secondary(reflect_ray, remaining, 1., weight, RayKind::Reflection)
+            }
+            None => Color::black(),
         }
+    }
 
-        let reflective = computation
-            .object
-            .get_props()
-            .get_material()
-            .get_reflective();
-        if reflective == 0. {
-            return Color::new(0., 0., 0.);
+    // the reflected ray and how much it contributes, if it is traced at all
+    fn reflection(&self, computation: &Computation, remaining: usize) -> Option<(Ray, f64)> {
+        let props = computation.object.get_props();
+        if remaining == 0 || !props.is_reflective_surface() {
+            return None;
         }
-        let reflect_ray = Ray::new(computation.over_point, computation.reflect_v);
-        let color = self.color_at(reflect_ray, remaining - 1);
 
-        color * reflective
+        Some((
+            Ray::new(computation.over_point, computation.reflect_v),
+            props.get_material().get_reflective(),
+        ))
     }
 
-    fn refracted_color(&self, computation: &Computation, remaining: usize) -> Color {
+    fn refracted_color(
+        &self,
+        computation: &Computation,
+        remaining: usize,
+        throughput: f64,
+    ) -> Color {
         if remaining <= 0 {
             return Color::black();
         }
@@ -65,115 +396,351 @@ impl World {
             Color::black()
         } else {
             let n_ratio = computation.n1 / computation.n2;
-            let cos_i = computation.eye_v.dot(computation.normal_v);
-            let sin2_t = n_ratio.powi(2) * (1. - cos_i.powi(2));
+            let direction = match (-computation.eye_v).refract(computation.normal_v, n_ratio) {
+                Some(direction) => direction,
+                None => return Color::black(),
+            };
+            let cos_t = -direction.dot(computation.normal_v);
+
+            let thickness = computation.object.thickness();
+            let refract_ray = if thickness > 0. {
+                // the ray crosses the sheet and leaves it parallel to the incoming ray,
+                // only shifted sideways
+                let exit_point = computation.under_point + direction * (thickness / cos_t);
+
+                Ray::new(exit_point, -computation.eye_v)
+            } else {
+                Ray::new(computation.under_point, direction)
+            };
+
+            let transparency = computation
+                .object
+                .get_props()
+                .get_material()
+                .get_transparency();
+
+            self.trace_secondary(
+                refract_ray,
+                remaining,
+                throughput,
+                transparency,
+                RayKind::Refraction,
+            )
+        }
+    }
 
-            if sin2_t > 1. {
-                return Color::black();
+    // color of a reflected or refracted ray, scaled by its weight. throughput is the weight of
+    // the path up to the surface the ray leaves from.
+    fn trace_secondary(
+        &self,
+        ray: Ray,
+        remaining: usize,
+        throughput: f64,
+        weight: f64,
+        kind: RayKind,
+    ) -> Color {
+        match self.roulette_weight(ray, remaining, throughput, weight) {
+            Some(weight) => {
+                self.count_ray(kind);
+                self.trace_ray(ray, remaining - 1, throughput * weight, None) * weight
             }
+            None => Color::black(),
+        }
+    }
 
-            let cos_t = (1. - sin2_t).sqrt();
-            let direction =
-                computation.normal_v * (n_ratio * cos_i - cos_t) - computation.eye_v * n_ratio;
-
-            let refract_ray = Ray::new(computation.under_point, direction);
+    // With russian roulette enabled, rays that add little to the pixel are terminated randomly
+    // (None) and the survivors are scaled up by 1 / p, so the expected color stays the same.
+    // The survival chance follows the whole path's throughput, not just this bounce.
+    fn roulette_weight(
+        &self,
+        ray: Ray,
+        remaining: usize,
+        throughput: f64,
+        weight: f64,
+    ) -> Option<f64> {
+        let roulette = match self.roulette {
+            Some(roulette) if remaining < roulette.min_remaining => roulette,
+            _ => return Some(weight),
+        };
 
-            self.color_at(refract_ray, remaining - 1)
-                * computation
-                    .object
-                    .get_props()
-                    .get_material()
-                    .get_transparency()
+        let p = (throughput * weight).clamp(0.05, 1.);
+        if roulette.random(ray, remaining) >= p {
+            return None;
         }
+
+        Some(weight / p)
     }
 
-    fn is_shadowed(&self, point: Tuple) -> bool {
-        let light_position = match self.light {
-            Some(light) => light.position,
-            None => return false,
-        };
+    fn is_shadowed(&self, point: Tuple, light: &dyn Light) -> bool {
+        self.is_occluded(point, light.position(), None)
+    }
+
+    // fraction of the light reaching the point past everything casting a shadow
+    fn light_visibility(&self, point: Tuple, light: &dyn Light) -> f64 {
+        light.intensity_at(point, &|target| self.is_occluded(point, target, None))
+    }
 
-        let direction_v = light_position - point;
-        let distance = direction_v.magnitude();
+    // fraction of short rays over the hemisphere around the normal which escape, using the
+    // same fixed spiral as soft shadows. The rays are cosine weighted, so occluders right
+    // above the surface count more than those at grazing angles.
+    fn ambient_visibility(&self, computation: &Computation) -> f64 {
+        let normal = computation.normal_v;
+        let visible = disk_spiral(normal, self.ao_samples, 0.)
+            .filter(|offset| {
+                let direction = *offset + normal * (1. - offset.dot(*offset)).sqrt();
+                let target = computation.over_point + direction * AO_DISTANCE;
+
+                !self.is_occluded(computation.over_point, target, None)
+            })
+            .count();
+
+        visible as f64 / self.ao_samples as f64
+    }
+
+    // checks if anything casting a shadow lies between point and target, except the ignored
+    // object
+    pub(crate) fn is_occluded(&self, point: Tuple, target: Tuple, ignore: Option<Uuid>) -> bool {
+        self.count_ray(RayKind::Shadow);
+        let direction_v = target - point;
+        let distance = direction_v.magnitude3();
         let direction = direction_v.normalize();
 
         let xs = self.intersect(Ray::new(point, direction));
 
-        match xs.hit() {
-            Some(hit) => hit.t < distance,
-            None => false,
+        for intersection in &xs {
+            if intersection.t <= 0.
+                || Some(intersection.object.get_id()) == ignore
+                || !intersection.object.get_props().casts_shadow()
+            {
+                continue;
+            }
+
+            return intersection.t < distance;
         }
+
+        false
     }
 
-    fn intersect(&self, ray: Ray) -> Intersections {
-        let mut xs = Intersections::new();
+    // one bounce of light from emissive objects, each sampled as a point light at its center
+    fn emissive_color(&self, computation: &Computation) -> Color {
+        let mut color = Color::black();
+        let material = computation.object.get_props().get_material();
+        let surface_color = material.surface_color(computation.object, computation.over_point);
 
         for object in &self.objects {
-            if let Some(i) = object.intersect(ray) {
-                xs.merge(i);
+            let emission = object.get_props().get_material().get_emission();
+            if emission == Color::black() || object.get_id() == computation.object.get_id() {
+                continue;
             }
+
+            let center = object.get_props().get_transform() * Tuple::point(0., 0., 0.);
+            if self.is_occluded(computation.over_point, center, Some(object.get_id())) {
+                continue;
+            }
+
+            color = color
+                + material.direct_lighting(
+                    surface_color,
+                    &PointLight::new(center, emission),
+                    computation.over_point,
+                    computation.eye_v,
+                    computation.normal_v,
+                );
+        }
+
+        color
+    }
+
+    // world space normal at the nearest hit, facing the ray origin, without shading
+    pub(crate) fn hit_normal(&self, ray: Ray, visible: Option<&[bool]>) -> Option<Tuple> {
+        let xs = self.intersect_visible(ray, visible);
+        let hit = xs.hit()?;
+
+        Some(hit.prepare_computation(ray, &xs).normal_v)
+    }
+
+    // quick look at the composition: ambient and diffuse lighting only, as if nothing cast
+    // shadows. Reflective and transparent materials show only their own color.
+    pub(crate) fn color_at_preview(&self, ray: Ray, visible: Option<&[bool]>) -> Color {
+        self.count_ray(RayKind::Primary);
+
+        let xs = self.intersect_visible(ray, visible);
+        let comp = match xs.hit() {
+            Some(hit) => hit.prepare_computation(ray, &xs),
+            None => return self.background_color(ray),
+        };
+        let material = comp.object.get_props().get_material();
+
+        if self.lights.is_empty() {
+            return material.ambient_lighting(comp.object, comp.over_point);
+        }
+
+        let color = material.surface_color(comp.object, comp.over_point);
+        let mut intensity = Color::black();
+        let mut diffuse = Color::black();
+        for light in &self.lights {
+            intensity = intensity + light.intensity();
+            diffuse = diffuse
+                + material.diffuse_lighting(color, light.as_ref(), comp.over_point, comp.normal_v);
         }
-        xs.sort();
+
+        material.ambient_color(comp.object, comp.over_point, intensity)
+            + diffuse
+            + material.get_emission()
+    }
+
+    // depth of the nearest hit per ray, without shading (much cheaper than color_at)
+    pub(crate) fn depth_prepass(&self, rays: &[Ray]) -> Vec<Option<f64>> {
+        rays.iter()
+            .map(|ray| self.intersect(*ray).hit().map(|hit| hit.t))
+            .collect()
+    }
+
+    // everything the ray hits in ascending order of t, e.g. to pick objects
+    pub(crate) fn intersect(&self, ray: Ray) -> Intersections<'_> {
+        self.intersect_visible(ray, None)
+    }
+
+    // only tests the objects marked in visible, see color_at_visible
+    fn intersect_visible(&self, ray: Ray, visible: Option<&[bool]>) -> Intersections<'_> {
+        let is_visible = |index: usize| {
+            visible.is_none_or(|visible| visible.get(index).copied().unwrap_or(true))
+        };
+        let mut xs = match &self.bvh {
+            Some(bvh) => {
+                // objects added after building the bvh are tested directly
+                let candidates = bvh.candidates(ray);
+                let added = bvh.len()..self.objects.len();
+                let mut xs = Intersections::with_capacity(2 * (candidates.len() + added.len()));
+
+                for index in candidates
+                    .into_iter()
+                    .chain(added)
+                    .filter(|i| is_visible(*i))
+                {
+                    let hit = self.objects.get(index).and_then(|o| o.intersect(ray));
+                    if let Some(i) = hit {
+                        xs.merge(i);
+                    }
+                }
+
+                xs
+            }
+            None => {
+                let mut xs = Intersections::with_capacity(2 * self.objects.len());
+
+                for (index, object) in self.objects.iter().enumerate() {
+                    if !is_visible(index) {
+                        continue;
+                    }
+                    if let Some(i) = object.intersect(ray) {
+                        xs.merge(i);
+                    }
+                }
+
+                xs
+            }
+        };
+        xs.sort_along(ray);
 
         xs
     }
 
     fn shade_hit(&self, computation: &Computation, remaining: usize) -> Color {
-        let is_shadowed = self.is_shadowed(computation.over_point);
-
-        // Todo: Fix unwrap
-        let surface = computation.object.get_props().get_material().lighting(
-            computation.object,
-            self.light.unwrap(),
-            computation.over_point,
-            computation.eye_v,
-            computation.normal_v,
-            is_shadowed,
-        );
-        let reflected = self.reflected_color(&computation, remaining);
-        let refracted = self.refracted_color(&computation, remaining);
+        let (surface, reflection) = self.shade_surface(computation, remaining, 1.);
 
-        let m = computation.object.get_props().get_material();
+        match reflection {
+            Some((reflect_ray, weight)) => {
+                surface
+                    + self.trace_secondary(reflect_ray, remaining, 1., weight, RayKind::Reflection)
+            }
+            None => surface,
+        }
+    }
+
+    // everything but the reflection, which is returned as ray and weight for the caller to
+    // trace. Fresnel already scales the weight for materials that reflect and refract, or
+    // that are marked as dielectric. throughput is the weight of the path reaching the surface.
+    fn shade_surface(
+        &self,
+        computation: &Computation,
+        remaining: usize,
+        throughput: f64,
+    ) -> (Color, Option<(Ray, f64)>) {
+        let material = computation.object.get_props().get_material();
+        let receives_shadow = computation.object.get_props().receives_shadow();
+        let surface = if self.lights.is_empty() {
+            material.ambient_lighting(computation.object, computation.over_point)
+        } else {
+            let lights: Vec<(&dyn Light, f64)> = self
+                .lights
+                .iter()
+                .map(|light| {
+                    let visibility = if receives_shadow {
+                        self.light_visibility(computation.over_point, light.as_ref())
+                    } else {
+                        1.
+                    };
+
+                    (light.as_ref(), visibility)
+                })
+                .collect();
+
+            material.lighting_multi(
+                computation.object,
+                &lights,
+                computation.over_point,
+                computation.eye_v,
+                computation.normal_v,
+            )
+        };
+        let surface = if self.ao_samples > 0 {
+            let intensity = if self.lights.is_empty() {
+                Color::white()
+            } else {
+                self.lights
+                    .iter()
+                    .fold(Color::black(), |sum, light| sum + light.intensity())
+            };
+            let ambient =
+                material.ambient_color(computation.object, computation.over_point, intensity);
+
+            surface - ambient * (1. - self.ambient_visibility(computation))
+        } else {
+            surface
+        };
+        let surface = if self.emissive_lighting {
+            surface + self.emissive_color(computation)
+        } else {
+            surface
+        };
+        let reflection = self.reflection(computation, remaining);
+        let refracted = self.refracted_color(computation, remaining, throughput);
 
-        if m.get_reflective() > 0. && m.get_transparency() > 0. {
-            let reflectance = computation.schlick();
+        let dielectric = material.get_transparency() > 0. || material.get_fresnel();
+        if computation.object.get_props().is_reflective_surface() && dielectric {
+            let (color, reflectance) = computation.fresnel_blend(surface, refracted);
+            let reflection = reflection.map(|(ray, weight)| (ray, weight * reflectance));
 
-            return surface + reflected * reflectance + refracted * (1. - reflectance);
+            return (color, reflection);
         }
 
-        surface + reflected + refracted
+        (surface + refracted, reflection)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::math::transformation::{scaling, translation};
-    use crate::math::tuple::Tuple;
+    use crate::math::transformation::{rotation_z, translation};
     use crate::pattern::{Pattern, PatternProps};
     use crate::primitives::plane::Plane;
-    use crate::primitives::sphere::Sphere;
     use crate::scene::shading::color::Color;
+    use crate::scene::shading::light::LightKind;
+    use crate::scene::shading::material::Material;
     use crate::scene::tracing::intersection::Intersection;
     use crate::scene::tracing::ray::Ray;
-
-    fn default_world() -> World {
-        let point_light = PointLight::new(Tuple::point(-10., 10., -10.), Color::new(1., 1., 1.));
-        let mut s1 = Sphere::new();
-        s1.mut_props().set_material_color(Color::new(0.8, 1., 0.6));
-        s1.mut_props().set_material_diffuse(0.7);
-        s1.mut_props().set_material_specular(0.2);
-
-        let mut s2 = Sphere::new();
-        s2.mut_props().set_transform(scaling(0.5, 0.5, 0.5));
-
-        let mut w = World::new();
-        w.light = Some(point_light);
-        w.objects.push(Box::new(s1));
-        w.objects.push(Box::new(s2));
-
-        w
-    }
+    use std::f64::consts::PI;
 
     #[derive(Copy, Clone, Debug)]
     struct TestPattern {
@@ -181,6 +748,10 @@ mod tests {
     }
 
     impl Pattern for TestPattern {
+        fn clone_box(&self) -> Box<dyn Pattern> {
+            Box::new(*self)
+        }
+
         fn pattern_at(&self, point: Tuple) -> Color {
             Color::new(point.x, point.y, point.z)
         }
@@ -200,17 +771,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_demo_world_matches_default_world_of_book() {
+        let w = World::demo();
+
+        assert_eq!(2, w.objects.len());
+        assert_eq!(1, w.lights.len());
+        assert_eq!(
+            Some(LightKind::Point(PointLight::new(
+                Tuple::point(-10., 10., -10.),
+                Color::new(1., 1., 1.)
+            ))),
+            w.lights[0].kind()
+        );
+
+        let s1 = w.objects[0].get_props();
+        let mut m = Material::new();
+        m.set_color(Color::new(0.8, 1., 0.6));
+        m.set_diffuse(0.7);
+        m.set_specular(0.2);
+        assert_eq!(&m, s1.get_material());
+        assert_eq!(M4::identity(), s1.get_transform());
+
+        let s2 = w.objects[1].get_props();
+        assert_eq!(&Material::new(), s2.get_material());
+        assert_eq!(scaling(0.5, 0.5, 0.5), s2.get_transform());
+    }
+
+    #[test]
+    fn test_validate_flags_non_invertible_transform() {
+        let mut w = World::demo();
+        let mut s = Sphere::new();
+        s.set_transform(scaling(0., 1., 1.));
+        let id = s.get_id();
+        w.add_object(s);
+
+        assert!(World::demo().validate().is_empty());
+        assert_eq!(vec![Warning::NonInvertibleTransform(id)], w.validate());
+    }
+
+    #[test]
+    fn test_validate_flags_missing_lights_and_bright_materials() {
+        let mut w = World::new();
+        let mut s = Sphere::glass();
+        s.mut_props().set_material_reflective(0.9);
+        let glass_id = s.get_id();
+        w.add_object(s);
+        let mut s = Sphere::new();
+        s.mut_props().set_transform(M4::from([f64::NAN; 16]));
+        let nan_id = s.get_id();
+        w.add_object(s);
+
+        assert_eq!(
+            vec![
+                Warning::NoLights,
+                Warning::ReflectiveAndTransparentAboveOne(glass_id),
+                Warning::NanTransform(nan_id),
+            ],
+            w.validate()
+        );
+    }
+
     #[test]
     fn test_creating_world() {
         let w = World::new();
 
-        assert!(w.light.is_none());
+        assert!(w.lights.is_empty());
         assert_eq!(w.objects.len(), 0);
     }
 
+    #[test]
+    fn test_adding_objects_and_lights_to_world() {
+        let mut w = World::new();
+        let s = Sphere::new();
+        let id = s.get_id();
+
+        w.add_object(s);
+        w.add_object(Plane::new());
+        w.add_light(PointLight::new(
+            Tuple::point(0., 10., 0.),
+            Color::new(1., 1., 1.),
+        ));
+
+        assert_eq!(2, w.objects.len());
+        assert_eq!(id, w.objects[0].get_id());
+        assert_eq!(1, w.lights.len());
+    }
+
     #[test]
     fn test_intersect_world_with_ray() {
-        let w = default_world();
+        let w = World::demo();
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 0.0, 1.));
 
         let xs = w.intersect(r);
@@ -222,9 +872,71 @@ mod tests {
         assert_eq!(6., xs.get(3).unwrap().t);
     }
 
+    #[test]
+    fn test_intersect_world_with_bvh_keeps_ascending_order() {
+        let mut w = World::demo();
+        w.build_bvh();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 0.0, 1.));
+
+        let ts: Vec<f64> = w.intersect(r).iter().map(|i| i.t).collect();
+
+        assert_eq!(vec![4., 4.5, 5.5, 6.], ts);
+    }
+
+    #[test]
+    fn test_changing_objects_after_building_bvh_matches_linear_scan() {
+        let ts = |w: &World, r: Ray| w.intersect(r).iter().map(|i| i.t).collect::<Vec<_>>();
+        let moved = Ray::new(Tuple::point(100., 0., -5.), Tuple::direction(0., 0., 1.));
+        let center = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+
+        let mut w = World::demo();
+        w.build_bvh();
+        w.object_mut(0)
+            .unwrap()
+            .mut_props()
+            .set_transform(translation(100., 0., 0.));
+        let mut linear = World::demo();
+        linear.objects[0]
+            .mut_props()
+            .set_transform(translation(100., 0., 0.));
+
+        assert_eq!(vec![4., 6.], ts(&w, moved));
+        assert_eq!(ts(&linear, moved), ts(&w, moved));
+        assert_eq!(ts(&linear, center), ts(&w, center));
+
+        w.build_bvh();
+        let id = w.objects()[1].get_id();
+        w.remove_object(id).unwrap();
+        linear.objects.remove(1);
+
+        assert_eq!(ts(&linear, center), ts(&w, center));
+        assert!(w.remove_object(id).is_none());
+    }
+
+    #[test]
+    fn test_depth_prepass_matches_hit_of_intersection() {
+        let w = World::demo();
+        let rays = [
+            Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.)),
+            Ray::new(Tuple::point(0., 0., 0.), Tuple::direction(0., 0., 1.)),
+            Ray::new(Tuple::point(0., 0.9, -5.), Tuple::direction(0., 0., 1.)),
+            Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 1., 0.)),
+        ];
+
+        let depths = w.depth_prepass(&rays);
+
+        assert_eq!(rays.len(), depths.len());
+        for (ray, depth) in rays.iter().zip(depths.iter()) {
+            let expected = w.intersect(*ray).hit().map(|hit| hit.t);
+            assert_eq!(expected, *depth);
+        }
+        assert_eq!(Some(4.), depths[0]);
+        assert_eq!(None, depths[3]);
+    }
+
     #[test]
     fn test_shading_an_intersection() {
-        let w = default_world();
+        let w = World::demo();
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 0.0, 1.));
         let shape = w.objects.first().unwrap();
         let i = Intersection::new(4., shape.as_ref());
@@ -238,11 +950,11 @@ mod tests {
 
     #[test]
     fn test_shading_an_intersection_from_inside() {
-        let mut w = default_world();
-        w.light = Some(PointLight::new(
+        let mut w = World::demo();
+        w.lights = vec![Box::new(PointLight::new(
             Tuple::point(0., 0.25, 0.),
             Color::new(1., 1., 1.),
-        ));
+        ))];
 
         let r = Ray::new(Tuple::point(0., 0., 0.), Tuple::direction(0.0, 0.0, 1.));
         let shape = w.objects.last().unwrap();
@@ -255,10 +967,32 @@ mod tests {
         assert_eq!(c, Color::new(0.90498, 0.90498, 0.90498));
     }
 
+    #[test]
+    fn test_shading_with_dyn_light_matches_concrete_point_light() {
+        let w = World::demo();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let xs = w.intersect(r);
+        let comps = xs.hit().unwrap().prepare_computation(r, &xs);
+
+        let light = w.lights[0].as_ref();
+        let in_shadow = w.is_shadowed(comps.over_point, light);
+        let concrete = comps.object.get_props().get_material().lighting(
+            comps.object,
+            light,
+            comps.over_point,
+            comps.eye_v,
+            comps.normal_v,
+            in_shadow,
+        );
+
+        assert_eq!(concrete, w.shade_hit(&comps, 0));
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), concrete);
+    }
+
     #[test]
     fn test_shade_hit_with_intersection_in_shadow() {
         let mut w = World::new();
-        w.light = Some(PointLight::new(
+        w.add_light(PointLight::new(
             Tuple::point(0., 0., -10.),
             Color::new(1., 1., 1.),
         ));
@@ -284,7 +1018,7 @@ mod tests {
 
     #[test]
     fn test_color_when_ray_miss() {
-        let w = default_world();
+        let w = World::demo();
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 1.0, 0.));
 
         let c = w.color_at(r, 4);
@@ -294,7 +1028,7 @@ mod tests {
 
     #[test]
     fn test_color_when_ray_hits() {
-        let w = default_world();
+        let w = World::demo();
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 0.0, 1.));
 
         let c = w.color_at(r, 4);
@@ -302,9 +1036,49 @@ mod tests {
         assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855));
     }
 
+    #[test]
+    fn test_object_with_non_invertible_transform_is_skipped() {
+        let mut w = World::demo();
+        let mut s = Sphere::new();
+        s.set_transform(translation(0., 0., -3.) * scaling(0., 0., 0.));
+        s.mut_props().set_material_color(Color::new(1., 0., 0.));
+        w.add_object(s);
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0.0, 0.0, 1.));
+
+        assert!(w.objects[2].intersect(r).is_none());
+        assert_eq!(
+            Tuple::direction(0., 1., 0.),
+            w.objects[2].normal_at(Tuple::point(0., 0., -3.))
+        );
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), w.color_at(r, 4));
+    }
+
+    #[test]
+    fn test_missing_rays_see_sky_background() {
+        let mut w = World::demo();
+        let mut sky = SkyBackground::new(Color::new(0.1, 0.3, 0.8), Color::new(0.8, 0.9, 1.));
+        sky.set_sun(
+            Tuple::direction(0., 1., 1.),
+            Color::new(10., 10., 10.),
+            0.05,
+        );
+        w.background = Some(sky);
+
+        let to_sun = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 1., 1.));
+        let to_zenith = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 1., 0.));
+        let to_sphere = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+
+        assert_eq!(Color::new(10., 10., 10.), w.color_at(to_sun, 4));
+        assert_eq!(Color::new(0.1, 0.3, 0.8), w.color_at(to_zenith, 4));
+        assert_eq!(
+            Color::new(0.38066, 0.47583, 0.2855),
+            w.color_at(to_sphere, 4)
+        );
+    }
+
     #[test]
     fn test_color_with_intersection_behind_ray() {
-        let mut w = default_world();
+        let mut w = World::demo();
         w.objects
             .first_mut()
             .unwrap()
@@ -332,53 +1106,79 @@ mod tests {
 
     #[test]
     fn test_no_shadow_when_nothing_collinear_with_point_and_light() {
-        let w = default_world();
+        let w = World::demo();
         let p = Tuple::point(0., 10., 0.);
 
-        assert!(!w.is_shadowed(p));
+        assert!(!w.is_shadowed(p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn test_shadow_when_object_is_between_point_and_light() {
-        let w = default_world();
+        let w = World::demo();
         let p = Tuple::point(10., -10., 10.);
 
-        assert!(w.is_shadowed(p));
+        assert!(w.is_shadowed(p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn test_no_shadow_when_object_is_behind_light() {
-        let w = default_world();
+        let w = World::demo();
         let p = Tuple::point(-20., 20., 20.);
 
-        assert!(!w.is_shadowed(p));
+        assert!(!w.is_shadowed(p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn test_no_shadow_when_object_is_behind_point() {
-        let w = default_world();
+        let w = World::demo();
         let p = Tuple::point(-2., 2., 2.);
 
-        assert!(!w.is_shadowed(p));
+        assert!(!w.is_shadowed(p, w.lights[0].as_ref()));
     }
 
     #[test]
-    fn test_reflected_color_for_non_reflective_material() {
-        let point_light = PointLight::new(Tuple::point(-10., 10., -10.), Color::new(1., 1., 1.));
-        let mut s1 = Sphere::new();
-        s1.mut_props().set_material_color(Color::new(0.8, 1., 0.6));
-        s1.mut_props().set_material_diffuse(0.7);
-        s1.mut_props().set_material_specular(0.2);
+    fn test_objects_without_shadow_do_not_shadow_point() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Tuple::point(0., 10., 0.),
+            Color::new(1., 1., 1.),
+        ));
+        w.add_object(Sphere::glass().transformed(translation(0., 5., 0.)));
+        let p = Tuple::point(0., 0., 0.);
 
-        let mut s2 = Sphere::new();
-        s2.mut_props().set_transform(scaling(0.5, 0.5, 0.5));
-        s2.mut_props().set_material_ambient(1.);
+        assert!(w.is_shadowed(p, w.lights[0].as_ref()));
+
+        w.objects[0].mut_props().set_casts_shadow(false);
+        assert!(!w.is_shadowed(p, w.lights[0].as_ref()));
+    }
 
+    #[test]
+    fn test_surface_without_received_shadow_is_lit_when_occluded() {
         let mut w = World::new();
-        w.light = Some(point_light);
-        w.objects.push(Box::new(s1));
+        w.add_light(PointLight::new(
+            Tuple::point(0., 0., -10.),
+            Color::new(1., 1., 1.),
+        ));
+        w.objects.push(Box::new(Sphere::new()));
+
+        let mut s2 = Sphere::new();
+        s2.mut_props().set_transform(translation(0., 0., 10.));
+        s2.mut_props().set_receives_shadow(false);
         w.objects.push(Box::new(s2));
 
+        let r = Ray::new(Tuple::point(0., 0., 5.), Tuple::direction(0., 0., 1.));
+        let i = Intersection::new(4., w.objects[1].as_ref());
+        let comps = i.prepare_computation(r, &Intersections::new());
+
+        assert!(w.is_shadowed(comps.over_point, w.lights[0].as_ref()));
+        assert_eq!(Color::new(1.9, 1.9, 1.9), w.shade_hit(&comps, 4));
+    }
+
+    #[test]
+    fn test_reflected_color_for_non_reflective_material() {
+        let mut w = World::demo();
+        w.objects[1].mut_props().set_material_ambient(1.);
+
         let r = Ray::new(Tuple::point(0., 0., 0.), Tuple::direction(0., 0., 1.));
         let shape = w.objects.last().unwrap();
         let i = Intersection::new(1., shape.as_ref());
@@ -392,7 +1192,7 @@ mod tests {
 
     #[test]
     fn test_reflected_color_for_reflective_material() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         let mut shape = Plane::new();
         shape.mut_props().set_material_reflective(0.5);
@@ -414,9 +1214,57 @@ mod tests {
         assert_eq!(color, Color::new(0.190332, 0.23791, 0.142749));
     }
 
+    #[test]
+    fn test_preview_ignores_shadows() {
+        let w = World::demo();
+        // the inner sphere is in the shadow of the outer one, but not in the preview
+        let r = Ray::new(Tuple::point(0., 0., -0.75), Tuple::direction(0., 0., 1.));
+
+        assert_eq!(Color::new(0.1, 0.1, 0.1), w.color_at(r, 4));
+        assert!(w.color_at_preview(r, None).red > 0.1);
+    }
+
+    #[test]
+    fn test_preview_leaves_out_specular_highlights() {
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+        let mut matte = World::demo();
+        matte.objects[0].mut_props().set_material_specular(0.);
+        let mut shiny = World::demo();
+        shiny.objects[0].mut_props().set_material_specular(0.9);
+        shiny.enable_stats();
+
+        assert_eq!(matte.color_at(r, 4), shiny.color_at_preview(r, None));
+        assert_eq!(1, shiny.stats().primary_rays);
+    }
+
+    #[test]
+    fn test_stats_count_reflection_and_shadow_rays() {
+        let mut w = World::demo();
+        let mut floor = Plane::new();
+        floor.mut_props().set_material_reflective(0.5);
+        floor.mut_props().set_transform(translation(0., -1., 0.));
+        w.objects.push(Box::new(floor));
+        let r = Ray::new(
+            Tuple::point(0., 0., -3.),
+            Tuple::direction(0., -2f64.sqrt() / 2., 2f64.sqrt() / 2.),
+        );
+
+        w.color_at(r, 4);
+        assert_eq!(RenderStats::default(), w.stats());
+
+        w.enable_stats();
+        w.color_at(r, 4);
+        let stats = w.stats();
+
+        assert_eq!(1, stats.primary_rays);
+        assert_eq!(1, stats.reflection_rays);
+        assert_eq!(0, stats.refraction_rays);
+        assert_eq!(2, stats.shadow_rays);
+    }
+
     #[test]
     fn test_shade_hit_for_reflective_material() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         let mut shape = Plane::new();
         shape.mut_props().set_material_reflective(0.5);
@@ -441,7 +1289,7 @@ mod tests {
     #[test]
     fn test_color_at_with_mutually_reflective_surfaces() {
         let mut w = World::new();
-        w.light = Some(PointLight::new(
+        w.add_light(PointLight::new(
             Tuple::point(0., 0., 0.),
             Color::new(1., 1., 1.),
         ));
@@ -463,9 +1311,38 @@ mod tests {
         assert!(true)
     }
 
+    #[test]
+    fn test_deep_mutual_reflections_do_not_grow_stack() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Tuple::point(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        ));
+        let mut lower = Plane::new();
+        lower.mut_props().set_material_reflective(1.);
+        lower.mut_props().set_transform(translation(0., -1., 0.));
+        let mut upper = Plane::new();
+        upper.mut_props().set_material_reflective(1.);
+        upper.mut_props().set_transform(translation(0., 1., 0.));
+        w.add_object(lower);
+        w.add_object(upper);
+
+        // every bounce sees the same lighting, so each one adds the same color
+        let r = Ray::new(Tuple::point(0., 0., 0.), Tuple::direction(0., 1., 0.));
+        let single = w.color_at(r, 0);
+        let xs = w.intersect(r);
+        let comps = xs.hit().unwrap().prepare_computation(r, &xs);
+
+        assert_eq!(single * 5., w.color_at(r, 4));
+        assert_eq!(w.shade_hit(&comps, 4), w.color_at(r, 4));
+
+        let deep = w.color_at(r, 100_000);
+        assert!((deep.red / single.red - 100_001.).abs() < 0.001);
+    }
+
     #[test]
     fn test_reflected_color_at_maximum_recursive_depth() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         let mut shape = Plane::new();
         shape.mut_props().set_material_reflective(0.5);
@@ -489,7 +1366,7 @@ mod tests {
 
     #[test]
     fn test_refracted_color_with_opaque_surface() {
-        let w = default_world();
+        let w = World::demo();
         let shape = w.objects.first().unwrap().as_ref();
         let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
 
@@ -498,14 +1375,14 @@ mod tests {
         xs.push(Intersection::new(6., shape));
 
         let comps = xs.first().unwrap().prepare_computation(r, &xs);
-        let c = w.refracted_color(&comps, 5);
+        let c = w.refracted_color(&comps, 5, 1.);
 
         assert_eq!(c, Color::black())
     }
 
     #[test]
     fn test_refracted_color_at_maximum_recursive_depth() {
-        let mut w = default_world();
+        let mut w = World::demo();
         w.objects
             .first_mut()
             .unwrap()
@@ -523,14 +1400,14 @@ mod tests {
         xs.push(Intersection::new(6., w.objects.first().unwrap().as_ref()));
 
         let comps = xs.first().unwrap().prepare_computation(r, &xs);
-        let c = w.refracted_color(&comps, 0);
+        let c = w.refracted_color(&comps, 0, 1.);
 
         assert_eq!(c, Color::black())
     }
 
     #[test]
     fn test_refracted_color_under_total_internal_reflection() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         w.objects
             .first_mut()
@@ -558,14 +1435,14 @@ mod tests {
         ));
 
         let comps = xs.last().unwrap().prepare_computation(r, &xs);
-        let c = w.refracted_color(&comps, 5);
+        let c = w.refracted_color(&comps, 5, 1.);
 
         assert_eq!(c, Color::black())
     }
 
     #[test]
     fn test_refracted_color_with_a_refracted_ray() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         w.objects
             .first_mut()
@@ -610,14 +1487,14 @@ mod tests {
         ));
 
         let comps = xs.get(2).unwrap().prepare_computation(r, &xs);
-        let c = w.refracted_color(&comps, 5);
+        let c = w.refracted_color(&comps, 5, 1.);
 
         assert_eq!(c, Color::new(0., 0.998874, 0.04721));
     }
 
     #[test]
     fn test_shade_hit_with_transparent_material() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         let mut floor = Plane::new();
         floor.mut_props().set_transform(translation(0., -1., 0.));
@@ -649,7 +1526,7 @@ mod tests {
 
     #[test]
     fn test_shade_hit_with_reflective_and_transparent_material() {
-        let mut w = default_world();
+        let mut w = World::demo();
 
         let mut floor = Plane::new();
         floor.mut_props().set_transform(translation(0., -1., 0.));
@@ -679,4 +1556,306 @@ mod tests {
         let color = w.shade_hit(&comps, 5);
         assert_eq!(color, Color::new(0.93391, 0.69643, 0.69243));
     }
+
+    #[test]
+    fn test_fresnel_reflective_surface_reflects_more_at_grazing_angle() {
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.mut_props().set_material_reflective(1.);
+        floor.mut_props().set_material_refractive_index(1.5);
+        floor.mut_props().set_material_fresnel(true);
+        w.objects.push(Box::new(floor));
+
+        let reflection_weight = |direction: Tuple| {
+            let r = Ray::new(Tuple::point(0., 1., 0.) - direction * 1., direction);
+            let xs = w.intersect(r);
+            let comps = xs.hit().unwrap().prepare_computation(r, &xs);
+
+            w.shade_surface(&comps, 5, 1.).1.unwrap().1
+        };
+
+        let head_on = reflection_weight(Tuple::direction(0., -1., 0.));
+        let grazing = reflection_weight(Tuple::direction(0., -0.1, 1.).normalize());
+
+        assert!(head_on < 0.05);
+        assert!(grazing > head_on);
+    }
+
+    #[test]
+    fn test_emissive_lighting_brightens_floor_beneath_glowing_sphere() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Tuple::point(-10., 10., -10.),
+            Color::new(0.2, 0.2, 0.2),
+        ));
+        w.objects.push(Box::new(Plane::new()));
+
+        let mut glow = Material::new();
+        glow.set_emission(Color::new(1., 1., 1.));
+        let mut orb = Sphere::new();
+        orb.mut_props().set_transform(translation(0., 2., 0.));
+        orb.mut_props().set_material(glow);
+        w.objects.push(Box::new(orb));
+
+        let r = Ray::new(
+            Tuple::point(0., 1., -3.),
+            Tuple::direction(0., -1., 3.).normalize(),
+        );
+
+        let self_only = w.color_at(r, 5);
+        w.emissive_lighting = true;
+        let lit = w.color_at(r, 5);
+
+        assert!(lit.red > self_only.red + 0.5);
+        assert!(lit.green > self_only.green + 0.5);
+        assert!(lit.blue > self_only.blue + 0.5);
+    }
+
+    #[test]
+    fn test_color_without_light_is_the_ambient_color() {
+        let mut w = World::demo();
+        w.lights.clear();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+
+        let c = w.color_at(r, 5);
+
+        assert_eq!(Color::new(0.08, 0.1, 0.06), c);
+    }
+
+    #[test]
+    fn test_ambient_occlusion_is_off_by_default() {
+        let w = World::demo();
+        let r = Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.));
+
+        assert_eq!(0, w.ao_samples);
+        assert_eq!(Color::new(0.38066, 0.47583, 0.2855), w.color_at(r, 5));
+    }
+
+    #[test]
+    fn test_ambient_occlusion_darkens_corner_between_two_planes() {
+        let mut w = World::new();
+        w.add_object(Plane::new());
+        let mut wall = Plane::new();
+        wall.mut_props().set_transform(rotation_z(PI / 2.));
+        w.add_object(wall);
+
+        let near_corner = Ray::new(Tuple::point(0.1, 1., 0.), Tuple::direction(0., -1., 0.));
+        let open_floor = Ray::new(Tuple::point(5., 1., 0.), Tuple::direction(0., -1., 0.));
+
+        let flat = w.color_at(near_corner, 5);
+        w.ao_samples = 32;
+        let occluded = w.color_at(near_corner, 5);
+
+        assert!(occluded.red < flat.red * 0.9);
+        assert_eq!(flat, w.color_at(open_floor, 5));
+    }
+
+    #[test]
+    fn test_intersections_are_identical_with_and_without_bvh() {
+        let mut w = World::demo();
+        w.objects.push(Box::new(Plane::new()));
+        for i in 0..10 {
+            let mut s = Sphere::new();
+            s.mut_props().set_transform(
+                translation(i as f64 - 5., (i % 3) as f64, (i % 4) as f64 * 2.)
+                    * scaling(0.6, 0.6, 0.6),
+            );
+            w.objects.push(Box::new(s));
+        }
+
+        let rays = [
+            Ray::new(Tuple::point(0., 0., -5.), Tuple::direction(0., 0., 1.)),
+            Ray::new(
+                Tuple::point(-6., 1., -5.),
+                Tuple::direction(1., 0., 1.).normalize(),
+            ),
+            Ray::new(
+                Tuple::point(-4., 0.5, -3.),
+                Tuple::direction(0.1, -0.2, 1.).normalize(),
+            ),
+            Ray::new(Tuple::point(0., 5., 0.), Tuple::direction(0., -1., 0.)),
+            Ray::new(Tuple::point(10., 10., 10.), Tuple::direction(1., 0., 0.)),
+        ];
+        let collect = |w: &World, ray: Ray| {
+            let xs = w.intersect(ray);
+            (0..xs.len())
+                .map(|i| {
+                    let x = xs.get(i).unwrap();
+                    (x.t, x.object.get_id())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let without_bvh: Vec<_> = rays.iter().map(|r| collect(&w, *r)).collect();
+        w.build_bvh();
+        let with_bvh: Vec<_> = rays.iter().map(|r| collect(&w, *r)).collect();
+
+        assert_eq!(without_bvh, with_bvh);
+        assert!(without_bvh.iter().any(|xs| xs.len() > 2));
+    }
+
+    #[test]
+    fn test_updating_object_transform_only_moves_that_object() {
+        let mut left = Sphere::new();
+        left.mut_props().set_transform(translation(-2., 0., 0.));
+        let left_id = left.get_id();
+        let mut right = Sphere::new();
+        right.mut_props().set_transform(translation(2., 0., 0.));
+
+        let mut w = World::new();
+        w.objects.push(Box::new(left));
+        w.objects.push(Box::new(right));
+        let mut w = Arc::new(w);
+
+        let left_ray = Ray::new(Tuple::point(-2., 0., -5.), Tuple::direction(0., 0., 1.));
+        let right_ray = Ray::new(Tuple::point(2., 0., -5.), Tuple::direction(0., 0., 1.));
+        let right_before = w.intersect(right_ray).hit().map(|h| h.t);
+
+        let shared = w.clone();
+        assert!(World::update_shared_object_transform(
+            &mut w,
+            left_id,
+            translation(-2., 0., 1.)
+        ));
+        assert!(!Arc::ptr_eq(&w, &shared));
+        assert_eq!(Some(4.), shared.intersect(left_ray).hit().map(|h| h.t));
+        drop(shared);
+
+        assert!(World::update_shared_object_transform(
+            &mut w,
+            left_id,
+            translation(-2., 0., 1.)
+        ));
+        assert!(!World::update_shared_object_transform(
+            &mut w,
+            Uuid::new_v4(),
+            translation(0., 0., 0.)
+        ));
+
+        assert_eq!(Some(5.), w.intersect(left_ray).hit().map(|h| h.t));
+        assert_eq!(right_before, w.intersect(right_ray).hit().map(|h| h.t));
+    }
+
+    #[test]
+    fn test_shadow_softness_gives_fractional_occlusion_at_shadow_edge() {
+        let mut blocker = Sphere::new();
+        blocker.mut_props().set_transform(translation(0., 5., 0.));
+        let mut w = World::new();
+        w.objects.push(Box::new(blocker));
+
+        let mut light = PointLight::new(Tuple::point(0., 10., 0.), Color::white());
+        // the edge of the hard shadow on the floor is at x = 10 / sqrt(24)
+        let edge = Tuple::point(10. / 24f64.sqrt(), 0., 0.);
+
+        for x in &[0., 1., 2., 2.1, 3., 5.] {
+            let visibility = w.light_visibility(Tuple::point(*x, 0., 0.), &light);
+            assert!(visibility == 0. || visibility == 1.);
+        }
+
+        light.shadow_softness = 1.;
+        let visibility = w.light_visibility(edge, &light);
+        assert!(visibility > 0. && visibility < 1.);
+        assert_eq!(0., w.light_visibility(Tuple::point(0., 0., 0.), &light));
+        assert_eq!(1., w.light_visibility(Tuple::point(8., 0., 0.), &light));
+    }
+
+    #[test]
+    fn test_thick_glass_sheet_shifts_refracted_background() {
+        let sheet_world = |thickness: f64| {
+            let mut sheet = Plane::new();
+            sheet.set_thickness(thickness);
+            sheet.mut_props().set_material_ambient(0.);
+            sheet.mut_props().set_material_diffuse(0.);
+            sheet.mut_props().set_material_specular(0.);
+            sheet.mut_props().set_material_transparency(1.);
+            sheet.mut_props().set_material_refractive_index(1.5);
+
+            let mut floor = Plane::new();
+            floor.mut_props().set_transform(translation(0., -2., 0.));
+            floor.mut_props().set_material_ambient(1.);
+            floor.mut_props().set_pattern(Box::new(TestPattern {
+                props: PatternProps::default(),
+            }));
+
+            let mut w = World::new();
+            w.objects.push(Box::new(sheet));
+            w.objects.push(Box::new(floor));
+            w
+        };
+        let r = Ray::new(
+            Tuple::point(0., 1., 0.),
+            Tuple::direction(1., -1., 0.).normalize(),
+        );
+
+        // inside the glass the ray runs at asin(sin(45°) / 1.5) to the normal
+        let inside = ((PI / 4.).sin() / 1.5).asin().tan();
+
+        let thin = sheet_world(0.).color_at(r, 5);
+        assert!((thin.red - (1. + 2. * inside)).abs() < 0.001);
+
+        let thick = sheet_world(0.5).color_at(r, 5);
+        assert!((thick.red - (1. + 0.5 * inside + 1.5)).abs() < 0.001);
+        assert!(thick.red > thin.red);
+    }
+
+    #[test]
+    fn test_russian_roulette_survival_follows_path_throughput() {
+        let mut w = World::new();
+        let r = Ray::new(Tuple::point(0., 0., 0.), Tuple::direction(0., 0., 1.));
+
+        let mut bright = 0;
+        let mut dim = 0;
+        for seed in 0..1000 {
+            w.roulette = Some(RussianRoulette {
+                min_remaining: 6,
+                seed,
+            });
+            if let Some(weight) = w.roulette_weight(r, 3, 1., 0.5) {
+                assert_eq!(1., weight);
+                bright += 1;
+            }
+            if let Some(weight) = w.roulette_weight(r, 3, 0.1, 0.5) {
+                assert_eq!(10., weight);
+                dim += 1;
+            }
+        }
+
+        assert!(dim < bright);
+        assert!(dim > 0);
+    }
+
+    #[test]
+    fn test_russian_roulette_matches_fixed_depth_on_average() {
+        let mut w = World::demo();
+        w.objects[0].mut_props().set_material_reflective(0.5);
+        let mut floor = Plane::new();
+        floor.mut_props().set_transform(translation(0., -1., 0.));
+        floor.mut_props().set_material_reflective(0.5);
+        w.objects.push(Box::new(floor));
+
+        let r = Ray::new(
+            Tuple::point(0., 0., -3.),
+            Tuple::direction(0., -2f64.sqrt() / 2., 2f64.sqrt() / 2.),
+        );
+        let fixed = w.color_at(r, 5);
+
+        let samples = 4000;
+        let mut sum = Color::black();
+        let mut terminated = false;
+        for seed in 0..samples {
+            w.roulette = Some(RussianRoulette {
+                min_remaining: 6,
+                seed,
+            });
+            let c = w.color_at(r, 5);
+            terminated = terminated || c != fixed;
+            sum = sum + c;
+        }
+        let mean = sum * (1. / samples as f64);
+
+        assert!(terminated);
+        assert!((mean.red - fixed.red).abs() < 0.01);
+        assert!((mean.green - fixed.green).abs() < 0.01);
+        assert!((mean.blue - fixed.blue).abs() < 0.01);
+    }
 }